@@ -0,0 +1,60 @@
+#!/usr/bin/env sh
+# 更新包签名工具，需要 OpenSSL 3.0 及以上版本
+#
+#   sign-release.sh keygen <私钥文件>       生成 Ed25519 私钥并输出公钥
+#   sign-release.sh pubkey <私钥文件>       输出私钥对应的公钥
+#   sign-release.sh sign <私钥文件> <目录>  为目录下的发布文件生成 SHA256SUMS 及 SHA256SUMS.sig
+#
+# 公钥为 32 字节的十六进制文本，需写入 src/resource/update/public_key.hex；
+# 私钥不要提交到仓库，发布流程中通过 UPDATE_SIGNING_KEY 密钥传入。
+set -eu
+
+to_hex() {
+    od -An -tx1 -v | tr -d ' \n'
+}
+
+public_key() {
+    # DER 格式公钥的最后 32 字节即为原始公钥
+    openssl pkey -in "$1" -pubout -outform DER | tail -c 32 | to_hex
+    echo
+}
+
+usage() {
+    sed -n '2,9p' "$0" >&2
+    exit 1
+}
+
+[ $# -ge 2 ] || usage
+
+case "$1" in
+    keygen)
+        if [ -e "$2" ]; then
+            echo "$2 已存在" >&2
+            exit 1
+        fi
+        (umask 077 && openssl genpkey -algorithm ed25519 -out "$2")
+        public_key "$2"
+        ;;
+    pubkey)
+        public_key "$2"
+        ;;
+    sign)
+        [ $# -eq 3 ] || usage
+        key=$(cd "$(dirname "$2")" && pwd)/$(basename "$2")
+        cd "$3"
+        rm -f SHA256SUMS SHA256SUMS.sig
+        manifest=$(mktemp)
+        for file in *; do
+            if [ -f "$file" ]; then
+                sha256sum "$file"
+            fi
+        done > "$manifest"
+        mv "$manifest" SHA256SUMS
+        # 对清单原文做 Ed25519 签名，签名以十六进制文本保存
+        openssl pkeyutl -sign -inkey "$key" -rawin -in SHA256SUMS | to_hex > SHA256SUMS.sig
+        cat SHA256SUMS
+        ;;
+    *)
+        usage
+        ;;
+esac
@@ -2,4 +2,13 @@ pub(crate) mod gacha;
 pub(crate) mod util;
 pub(crate) mod statistics;
 pub(crate) mod message;
-pub(crate) mod update;
\ No newline at end of file
+pub(crate) mod update;
+pub(crate) mod install;
+pub(crate) mod http;
+pub(crate) mod error;
+pub(crate) mod resource;
+pub(crate) mod region;
+pub(crate) mod account;
+pub(crate) mod export;
+pub(crate) mod report;
+pub(crate) mod roster;
@@ -0,0 +1,313 @@
+use std::fs;
+use std::path::Path;
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use crate::core::error::CoreError;
+use crate::core::gacha::load_saved_gacha_file;
+use crate::core::region::Region;
+use crate::core::util::get_request_param;
+
+// 用户数据根目录
+pub(crate) const DATA_DIR: &str = "./data";
+// 删除的用户数据移动到该目录中，不是 UID 不会被识别为用户
+const TRASH_DIR: &str = ".trash";
+
+// 用户自定义设置，保存在用户目录下
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+struct AccountSettings {
+    nickname: String,
+    hidden: bool,
+}
+
+/// 用户信息
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct AccountInfo {
+    pub(crate) player_id: String,
+    pub(crate) nickname: String,
+    pub(crate) hidden: bool,
+    // 由缓存的抽卡链接判断，没有缓存链接时为空
+    pub(crate) region: Option<Region>,
+    // 抽卡数据文件的最后修改时间
+    pub(crate) last_fetch: Option<DateTime<Utc>>,
+    // 各卡池的记录数
+    pub(crate) record_counts: Vec<(i32, usize)>,
+    // 数据文件无法解析
+    pub(crate) corrupted: bool,
+}
+
+impl AccountInfo {
+    pub(crate) fn record_count(&self) -> usize {
+        self.record_counts.iter().map(|(_, count)| count).sum()
+    }
+}
+
+/// 对用户执行的操作
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum AccountAction {
+    Rename(String),
+    SetHidden(bool),
+    // 移动到数据目录下的回收站
+    Delete,
+    // 替换缓存的抽卡链接
+    Relink(String),
+}
+
+fn account_dir(data_dir: &str, player_id: &str) -> String {
+    format!("{}/{}", data_dir, player_id)
+}
+
+fn settings_path(data_dir: &str, player_id: &str) -> String {
+    format!("{}/account.toml", account_dir(data_dir, player_id))
+}
+
+fn load_settings(data_dir: &str, player_id: &str) -> AccountSettings {
+    fs::read_to_string(settings_path(data_dir, player_id))
+        .ok()
+        .and_then(|buffer| toml::from_str(&buffer).ok())
+        .unwrap_or_default()
+}
+
+fn save_settings(data_dir: &str, player_id: &str, settings: &AccountSettings) -> Result<(), CoreError> {
+    let path = settings_path(data_dir, player_id);
+    let buffer = toml::to_string(settings).map_err(|err| CoreError::Internal(err.to_string()))?;
+    fs::write(&path, buffer).map_err(|err| CoreError::io(&path, err))
+}
+
+/// 根据缓存的抽卡链接判断用户所在区服
+pub(crate) fn account_region(player_id: &str) -> Option<Region> {
+    account_region_in(DATA_DIR, player_id)
+}
+
+fn account_region_in(data_dir: &str, player_id: &str) -> Option<Region> {
+    let url = fs::read_to_string(format!("{}/url_cache.txt", account_dir(data_dir, player_id))).ok()?;
+    let (oversea, param) = get_request_param(url).ok()?;
+    Some(Region::from_server(oversea, &param.server_id))
+}
+
+// 用户目录以 UID 命名，数据目录下的图标、回收站等其他目录均不是用户
+fn is_player_id(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+// 数据目录下的全部用户目录，跳过回收站、图标等内部目录及无法识别的目录名
+fn player_dirs(data_dir: &str) -> Result<Vec<String>, CoreError> {
+    let _ = fs::create_dir_all(data_dir);
+    let entries = fs::read_dir(data_dir).map_err(|err| CoreError::io(data_dir, err))?;
+
+    let mut player_ids = vec![];
+    for dir in entries.filter_map(Result::ok) {
+        if !dir.path().is_dir() {
+            continue;
+        }
+        let Ok(player_id) = dir.file_name().into_string() else {
+            warn!("无法识别的用户目录：{:?}", dir.file_name());
+            continue;
+        };
+        if !is_player_id(&player_id) {
+            continue;
+        }
+        player_ids.push(player_id);
+    }
+
+    player_ids.sort();
+    Ok(player_ids)
+}
+
+/// 数据目录下未隐藏的用户
+pub(crate) fn visible_player_ids(data_dir: &str) -> Result<Vec<String>, CoreError> {
+    Ok(player_dirs(data_dir)?
+        .into_iter()
+        .filter(|player_id| !load_settings(data_dir, player_id).hidden)
+        .collect())
+}
+
+/// 数据目录下的全部用户，包含已隐藏的用户
+pub(crate) fn list_accounts() -> Result<Vec<AccountInfo>, CoreError> {
+    list_accounts_in(DATA_DIR)
+}
+
+fn list_accounts_in(data_dir: &str) -> Result<Vec<AccountInfo>, CoreError> {
+    Ok(player_dirs(data_dir)?
+        .into_iter()
+        .map(|player_id| account_info(data_dir, player_id))
+        .collect())
+}
+
+fn account_info(data_dir: &str, player_id: String) -> AccountInfo {
+    let settings = load_settings(data_dir, &player_id);
+    let region = account_region_in(data_dir, &player_id);
+    let gacha_data_path = format!("{}/gacha_data.json", account_dir(data_dir, &player_id));
+    let last_fetch = fs::metadata(&gacha_data_path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .map(DateTime::<Utc>::from);
+
+    let (record_counts, corrupted) = if Path::new(&gacha_data_path).exists() {
+        // 旧版本数据需要按区服转换时间，无法判断区服时按国服处理
+        match load_saved_gacha_file(&gacha_data_path, region.unwrap_or(Region::China)) {
+            Ok(saved_gacha_data) => (
+                saved_gacha_data
+                    .iter()
+                    .map(|(card_pool_type, data)| (*card_pool_type, data.len()))
+                    .collect(),
+                false,
+            ),
+            Err(err) => {
+                warn!("用户 {} 的数据无法读取：{}", player_id, err);
+                (vec![], true)
+            }
+        }
+    } else {
+        (vec![], false)
+    };
+
+    AccountInfo {
+        region,
+        nickname: settings.nickname,
+        hidden: settings.hidden,
+        player_id,
+        last_fetch,
+        record_counts,
+        corrupted,
+    }
+}
+
+/// 执行用户操作
+pub(crate) fn apply_account_action(player_id: &str, action: AccountAction) -> Result<(), CoreError> {
+    apply_account_action_in(DATA_DIR, player_id, action)
+}
+
+fn apply_account_action_in(data_dir: &str, player_id: &str, action: AccountAction) -> Result<(), CoreError> {
+    let dir = account_dir(data_dir, player_id);
+    if !is_player_id(player_id) || !Path::new(&dir).is_dir() {
+        return Err(CoreError::io(&dir, std::io::ErrorKind::NotFound.into()));
+    }
+
+    match action {
+        AccountAction::Rename(nickname) => {
+            let mut settings = load_settings(data_dir, player_id);
+            settings.nickname = nickname.trim().to_string();
+            save_settings(data_dir, player_id, &settings)
+        }
+        AccountAction::SetHidden(hidden) => {
+            let mut settings = load_settings(data_dir, player_id);
+            settings.hidden = hidden;
+            save_settings(data_dir, player_id, &settings)
+        }
+        AccountAction::Delete => {
+            let trash_dir = format!("{}/{}", data_dir, TRASH_DIR);
+            fs::create_dir_all(&trash_dir).map_err(|err| CoreError::io(&trash_dir, err))?;
+            let target = format!("{}/{}-{}", trash_dir, player_id, Local::now().format("%Y-%m-%d-%H-%M-%S"));
+            info!("删除用户 {}，数据移动到 {}", player_id, target);
+            fs::rename(&dir, &target).map_err(|err| CoreError::io(&dir, err))
+        }
+        AccountAction::Relink(url) => {
+            let url = url.trim().to_string();
+            let (_, param) = get_request_param(url.clone())?;
+            // 链接必须属于该用户，避免数据混入其他用户
+            if param.player_id != player_id {
+                return Err(CoreError::AccountMismatch {
+                    expected: player_id.to_string(),
+                    actual: param.player_id,
+                });
+            }
+            let path = format!("{}/url_cache.txt", dir);
+            fs::write(&path, url).map_err(|err| CoreError::io(&path, err))
+        }
+    }
+}
+
+#[test]
+fn account_settings_test() {
+    // 旧版本没有设置文件，缺少的字段使用默认值
+    let settings: AccountSettings = toml::from_str("nickname = \"小号\"").unwrap();
+    assert_eq!(settings, AccountSettings { nickname: "小号".to_string(), hidden: false });
+
+    let settings = AccountSettings { nickname: String::new(), hidden: true };
+    let buffer = toml::to_string(&settings).unwrap();
+    assert_eq!(toml::from_str::<AccountSettings>(&buffer).unwrap(), settings);
+
+    let info = AccountInfo { record_counts: vec![(1, 80), (2, 20)], ..Default::default() };
+    assert_eq!(info.record_count(), 100);
+}
+
+#[test]
+fn apply_account_action_test() {
+    let data_dir = std::env::temp_dir().join(format!("wwgr-account-test-{}", std::process::id()));
+    let data_dir = data_dir.to_str().unwrap();
+    let _ = fs::remove_dir_all(data_dir);
+    for player_id in ["100000001", "100000002"] {
+        fs::create_dir_all(account_dir(data_dir, player_id)).unwrap();
+    }
+    // 无法识别的目录名不视为用户
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        fs::create_dir_all(Path::new(data_dir).join(std::ffi::OsStr::from_bytes(b"\xff"))).unwrap();
+    }
+
+    // 隐藏的用户不出现在用户列表中，但仍在用户管理中展示
+    apply_account_action_in(data_dir, "100000002", AccountAction::SetHidden(true)).unwrap();
+    assert_eq!(visible_player_ids(data_dir).unwrap(), vec!["100000001"]);
+    let accounts: Vec<_> = list_accounts_in(data_dir)
+        .unwrap()
+        .into_iter()
+        .map(|account| (account.player_id, account.hidden))
+        .collect();
+    assert_eq!(accounts, vec![("100000001".to_string(), false), ("100000002".to_string(), true)]);
+    apply_account_action_in(data_dir, "100000002", AccountAction::SetHidden(false)).unwrap();
+    assert_eq!(visible_player_ids(data_dir).unwrap(), vec!["100000001", "100000002"]);
+
+    // 抽卡链接属于其他用户时不替换
+    let url = |player_id: &str| format!(
+        "https://aki-gm-resources.aki-game.com/aki/gacha/index.html#/record?svr_id=76402e5b20be2c39f095a152090afddc&player_id={}&lang=zh-Hans&gacha_id=100003&gacha_type=1&svr_area=cn&record_id=record&resources_id=resources",
+        player_id
+    );
+    let url_cache_path = format!("{}/url_cache.txt", account_dir(data_dir, "100000001"));
+    let result = apply_account_action_in(data_dir, "100000001", AccountAction::Relink(url("100000002")));
+    assert!(matches!(result, Err(CoreError::AccountMismatch { expected, actual }) if expected == "100000001" && actual == "100000002"));
+    assert!(!Path::new(&url_cache_path).exists());
+    apply_account_action_in(data_dir, "100000001", AccountAction::Relink(url("100000001"))).unwrap();
+    assert_eq!(fs::read_to_string(&url_cache_path).unwrap(), url("100000001"));
+    assert_eq!(account_region_in(data_dir, "100000001"), Some(Region::China));
+
+    // 删除的用户移动到回收站
+    apply_account_action_in(data_dir, "100000001", AccountAction::Delete).unwrap();
+    assert!(!Path::new(&account_dir(data_dir, "100000001")).exists());
+    let trash: Vec<_> = fs::read_dir(format!("{}/{}", data_dir, TRASH_DIR))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    assert_eq!(trash.len(), 1);
+    assert!(trash[0].starts_with("100000001-"));
+    assert_eq!(visible_player_ids(data_dir).unwrap(), vec!["100000002"]);
+    assert_eq!(list_accounts_in(data_dir).unwrap().len(), 1);
+
+    // 不能对回收站或不存在的用户执行操作
+    assert!(apply_account_action_in(data_dir, TRASH_DIR, AccountAction::Delete).is_err());
+    assert!(apply_account_action_in(data_dir, "100000001", AccountAction::Delete).is_err());
+
+    fs::remove_dir_all(data_dir).unwrap();
+}
+
+#[test]
+fn player_dirs_test() {
+    let data_dir = std::env::temp_dir().join(format!("wwgr-player-dirs-test-{}", std::process::id()));
+    let data_dir = data_dir.to_str().unwrap();
+    let _ = fs::remove_dir_all(data_dir);
+    // 放入图标后 data/icons 不能被当作用户
+    for dir in ["100000001", "icons", TRASH_DIR, "100000002"] {
+        fs::create_dir_all(format!("{}/{}", data_dir, dir)).unwrap();
+    }
+    fs::write(format!("{}/config.toml", data_dir), "").unwrap();
+
+    assert_eq!(player_dirs(data_dir).unwrap(), vec!["100000001", "100000002"]);
+    assert_eq!(visible_player_ids(data_dir).unwrap(), vec!["100000001", "100000002"]);
+    assert!(list_accounts_in(data_dir).unwrap().iter().all(|account| account.player_id != "icons"));
+    assert!(apply_account_action_in(data_dir, "icons", AccountAction::Delete).is_err());
+    assert!(Path::new(&format!("{}/icons", data_dir)).is_dir());
+
+    fs::remove_dir_all(data_dir).unwrap();
+}
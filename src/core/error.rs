@@ -0,0 +1,137 @@
+use thiserror::Error;
+use crate::i18n::{tr, tr_args};
+
+/// 核心模块的错误类型，界面根据错误种类展示对应的提示和处理建议
+///
+/// 错误文本均取自翻译目录，日志及界面使用相同的文本
+#[derive(Error, Debug)]
+pub(crate) enum CoreError {
+    #[error("{}", self.describe().0)]
+    GameNotFound,
+    #[error("{}", self.describe().0)]
+    UrlNotFound { log_file: String },
+    #[error("{}", self.describe().0)]
+    UrlExpired { code: i32, message: String },
+    #[error("{}", self.describe().0)]
+    InvalidUrl(#[from] url::ParseError),
+    #[error("{}", self.describe().0)]
+    UnsupportedHost { host: String },
+    #[error("{}", self.describe().0)]
+    Network(#[from] reqwest::Error),
+    #[error("{}", self.describe().0)]
+    StorageCorrupted {
+        path: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("{}", self.describe().0)]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("{}", self.describe().0)]
+    InvalidTime(String),
+    #[error("{}", self.describe().0)]
+    NoCache,
+    #[error("{}", self.describe().0)]
+    UpdateAssetMissing,
+    #[error("{}", self.describe().0)]
+    UpdateManifestMissing,
+    #[error("{}", self.describe().0)]
+    UpdateSignatureInvalid,
+    #[error("{}", self.describe().0)]
+    UpdateChecksumMismatch {
+        asset: String,
+        expected: String,
+        actual: String,
+    },
+    #[error("{}", self.describe().0)]
+    UpdateArchiveInvalid { path: String, detail: String },
+    #[error("{}", self.describe().0)]
+    UpdateSmokeCheckFailed { detail: String },
+    #[error("{}", self.describe().0)]
+    AccountMismatch { expected: String, actual: String },
+    #[error("{}", self.describe().0)]
+    ExportFailed { path: String, detail: String },
+    #[error("{}", self.describe().0)]
+    FetchCancelled,
+    #[error("{}", self.describe().0)]
+    DownloadCancelled,
+    #[error("{}", self.describe().0)]
+    DownloadResumeFailed { detail: String },
+    #[error("{}", self.describe().0)]
+    Config(String),
+    // 程序自身的问题，与用户的配置及数据无关
+    #[error("{}", self.describe().0)]
+    Internal(String),
+}
+
+impl CoreError {
+    pub(crate) fn io(path: impl Into<String>, source: std::io::Error) -> Self {
+        Self::Io { path: path.into(), source }
+    }
+
+    pub(crate) fn corrupted(path: impl Into<String>, source: serde_json::Error) -> Self {
+        Self::StorageCorrupted { path: path.into(), source }
+    }
+
+    // 错误在翻译目录 error 分组中的键，处理建议的键为 `{key}_hint`
+    pub(crate) fn catalogue_key(&self) -> &'static str {
+        match self {
+            CoreError::GameNotFound => "game_not_found",
+            CoreError::UrlNotFound { .. } => "url_not_found",
+            CoreError::UrlExpired { .. } => "url_expired",
+            CoreError::InvalidUrl(_) => "invalid_url",
+            CoreError::UnsupportedHost { .. } => "unsupported_host",
+            CoreError::Network(_) => "network",
+            CoreError::StorageCorrupted { .. } => "storage_corrupted",
+            CoreError::Io { .. } => "io",
+            CoreError::InvalidTime(_) => "invalid_time",
+            CoreError::NoCache => "no_cache",
+            CoreError::UpdateAssetMissing => "update_asset_missing",
+            CoreError::UpdateManifestMissing => "update_manifest_missing",
+            CoreError::UpdateSignatureInvalid => "update_signature_invalid",
+            CoreError::UpdateChecksumMismatch { .. } => "update_checksum_mismatch",
+            CoreError::UpdateArchiveInvalid { .. } => "update_archive_invalid",
+            CoreError::UpdateSmokeCheckFailed { .. } => "update_smoke_check_failed",
+            CoreError::AccountMismatch { .. } => "account_mismatch",
+            CoreError::ExportFailed { .. } => "export_failed",
+            CoreError::FetchCancelled => "fetch_cancelled",
+            CoreError::DownloadCancelled => "download_cancelled",
+            CoreError::DownloadResumeFailed { .. } => "download_resume_failed",
+            CoreError::Config(_) => "config",
+            CoreError::Internal(_) => "internal",
+        }
+    }
+
+    // 错误提示中的占位符参数
+    pub(crate) fn catalogue_args(&self) -> Vec<(&'static str, String)> {
+        match self {
+            CoreError::UrlExpired { code, .. } => vec![("code", code.to_string())],
+            CoreError::UnsupportedHost { host } => vec![("host", host.to_string())],
+            CoreError::Network(err) => vec![("detail", err.to_string())],
+            CoreError::StorageCorrupted { path, .. } => vec![("path", path.to_string())],
+            CoreError::Io { path, source } => vec![("path", path.to_string()), ("detail", source.to_string())],
+            CoreError::InvalidTime(time) => vec![("time", time.to_string())],
+            CoreError::UpdateChecksumMismatch { asset, .. } => vec![("asset", asset.to_string())],
+            CoreError::UpdateArchiveInvalid { detail, .. } => vec![("detail", detail.to_string())],
+            CoreError::UpdateSmokeCheckFailed { detail } => vec![("detail", detail.to_string())],
+            CoreError::AccountMismatch { expected, actual } => vec![("expected", expected.to_string()), ("actual", actual.to_string())],
+            CoreError::ExportFailed { path, detail } => vec![("path", path.to_string()), ("detail", detail.to_string())],
+            CoreError::DownloadResumeFailed { detail } => vec![("detail", detail.to_string())],
+            CoreError::Config(message) => vec![("detail", message.to_string())],
+            CoreError::Internal(message) => vec![("detail", message.to_string())],
+            _ => vec![],
+        }
+    }
+
+    /// 界面展示的错误提示及处理建议，文本取自翻译目录
+    pub(crate) fn describe(&self) -> (String, String) {
+        let key = self.catalogue_key();
+        (
+            tr_args(&format!("error.{}", key), &self.catalogue_args()),
+            tr(&format!("error.{}_hint", key)),
+        )
+    }
+}
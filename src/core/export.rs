@@ -0,0 +1,507 @@
+use std::f32::consts::TAU;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use ab_glyph::{point, Font, FontArc, PxScale, ScaleFont};
+use chrono::Local;
+use image::{Rgba, RgbaImage};
+use tracing::{info, warn};
+use crate::core::error::CoreError;
+use crate::core::gacha::load_saved_gacha_data;
+use crate::core::region::DisplayTimeZone;
+use crate::core::report::{render_html, render_markdown};
+use crate::core::resource::resource_name;
+use crate::fonts::BUNDLED_FONT;
+use crate::core::statistics::{GachaStatistics, StatisticsSummary};
+use crate::i18n;
+use crate::i18n::{card_pool_name, t};
+
+// 摘要卡片的布局尺寸
+const WIDTH: f32 = 720.0;
+const PADDING: f32 = 24.0;
+const HEADER_HEIGHT: f32 = 96.0;
+const PIE_RADIUS: f32 = 60.0;
+// 卡池卡片中文字区域的起始位置
+const TEXT_OFFSET: f32 = 170.0;
+
+const BACKGROUND: [u8; 4] = [31, 36, 48, 255];
+const CARD_BACKGROUND: [u8; 4] = [42, 49, 64, 255];
+const TEXT_COLOR: [u8; 4] = [232, 232, 232, 255];
+const WEAK_TEXT_COLOR: [u8; 4] = [160, 168, 184, 255];
+const EMPTY_COLOR: [u8; 4] = [90, 96, 110, 255];
+// 与界面中统计图的颜色一致
+const STAR_COLORS: [[u8; 4]; 3] = [[99, 176, 225, 255], [171, 101, 207, 255], [225, 216, 115, 255]];
+
+/// 导出文件的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExportFormat {
+    Png,
+    Svg,
+    Html,
+    Markdown,
+}
+
+impl ExportFormat {
+    pub(crate) fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Png => "png",
+            ExportFormat::Svg => "svg",
+            ExportFormat::Html => "html",
+            ExportFormat::Markdown => "md",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct ExportOptions {
+    // 隐藏 UID 中间的数字，便于分享
+    pub(crate) mask_uid: bool,
+    // HTML 报告中抽卡记录时间的展示时区
+    pub(crate) time_zone: DisplayTimeZone,
+}
+
+// 卡片中的图形，坐标以左上角为原点
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    Rect {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        radius: f32,
+        color: [u8; 4],
+    },
+    // 角度从正上方开始按顺时针计算
+    Sector {
+        cx: f32,
+        cy: f32,
+        radius: f32,
+        start: f32,
+        end: f32,
+        color: [u8; 4],
+    },
+    // y 为文字基线位置
+    Text {
+        x: f32,
+        y: f32,
+        size: f32,
+        color: [u8; 4],
+        bold: bool,
+        text: String,
+    },
+}
+
+/// 与渲染方式无关的摘要卡片
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SummaryCard {
+    width: f32,
+    height: f32,
+    shapes: Vec<Shape>,
+}
+
+/// 隐藏 UID 中间的数字，只保留首尾各两位
+pub(crate) fn mask_uid(player_id: &str) -> String {
+    let chars: Vec<char> = player_id.chars().collect();
+    if chars.len() <= 4 {
+        return "*".repeat(chars.len());
+    }
+    let mut masked: String = chars[..2].iter().collect();
+    masked.push_str(&"*".repeat(chars.len() - 4));
+    masked.extend(&chars[chars.len() - 2..]);
+    masked
+}
+
+// 估算文字宽度，用于换行，两种渲染方式使用相同的估算保证布局一致
+fn text_width(text: &str, size: f32) -> f32 {
+    text.chars().map(|c| if c.is_ascii() { 0.55 } else { 1.0 }).sum::<f32>() * size
+}
+
+// 按最大宽度将条目排成多行
+fn wrap_items(items: &[String], size: f32, max_width: f32) -> Vec<String> {
+    let mut lines = vec![];
+    let mut line = String::new();
+    for item in items {
+        let candidate = if line.is_empty() { item.clone() } else { format!("{}  {}", line, item) };
+        if !line.is_empty() && text_width(&candidate, size) > max_width {
+            lines.push(std::mem::replace(&mut line, item.clone()));
+        } else {
+            line = candidate;
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+fn text(x: f32, y: f32, size: f32, color: [u8; 4], text: String) -> Shape {
+    Shape::Text { x, y, size, color, bold: false, text }
+}
+
+/// 生成用户抽卡统计的摘要卡片
+pub(crate) fn summary_card(player_id: &str, statistics: &GachaStatistics, options: ExportOptions) -> SummaryCard {
+    let mut shapes = vec![];
+    let player_id = if options.mask_uid { mask_uid(player_id) } else { player_id.to_string() };
+
+    shapes.push(Shape::Text {
+        x: PADDING,
+        y: PADDING + 30.0,
+        size: 30.0,
+        color: TEXT_COLOR,
+        bold: true,
+        text: t!("export.title"),
+    });
+    shapes.push(text(
+        PADDING,
+        PADDING + 60.0,
+        16.0,
+        WEAK_TEXT_COLOR,
+        t!("export.subtitle", uid = player_id, date = Local::now().format("%Y-%m-%d")),
+    ));
+
+    let card_width = WIDTH - PADDING * 2.0;
+    let mut y = HEADER_HEIGHT;
+    for (card_pool_type, data) in statistics.iter() {
+        let summary = StatisticsSummary::from_data(data);
+        let five_stars: Vec<String> = data
+            .detail
+            .iter()
+            .map(|item| format!("{}[{}]", resource_name(item.resource_id, i18n::language(), &item.name), item.count))
+            .collect();
+        let lines = wrap_items(&five_stars, 15.0, card_width - TEXT_OFFSET - PADDING);
+
+        let card_height = (20.0 + 30.0 + 24.0 * 2.0 + 22.0 * lines.len() as f32 + 16.0).max(PIE_RADIUS * 2.0 + 40.0);
+        shapes.push(Shape::Rect {
+            x: PADDING,
+            y,
+            width: card_width,
+            height: card_height,
+            radius: 10.0,
+            color: CARD_BACKGROUND,
+        });
+
+        // 星级占比饼图
+        let (cx, cy) = (PADDING + 20.0 + PIE_RADIUS, y + card_height / 2.0);
+        let counts = [data.three_count, data.four_count, data.five_count];
+        let total: i32 = counts.iter().sum();
+        if total == 0 {
+            shapes.push(Shape::Sector { cx, cy, radius: PIE_RADIUS, start: 0.0, end: TAU, color: EMPTY_COLOR });
+        }
+        let mut start = 0.0;
+        for (count, color) in counts.iter().zip(STAR_COLORS) {
+            if *count == 0 || total == 0 {
+                continue;
+            }
+            let end = start + TAU * *count as f32 / total as f32;
+            shapes.push(Shape::Sector { cx, cy, radius: PIE_RADIUS, start, end, color });
+            start = end;
+        }
+
+        let x = PADDING + TEXT_OFFSET;
+        let mut line_y = y + 20.0 + 22.0;
+        shapes.push(Shape::Text {
+            x,
+            y: line_y,
+            size: 22.0,
+            color: TEXT_COLOR,
+            bold: true,
+            text: card_pool_name(*card_pool_type),
+        });
+        line_y += 28.0;
+        shapes.push(text(
+            x,
+            line_y,
+            16.0,
+            TEXT_COLOR,
+            t!("label.pool_summary", total = data.total, pull = data.pull_count, five = data.five_count),
+        ));
+        line_y += 24.0;
+        let average = summary
+            .average_pity()
+            .map(|pity| format!("{:.1}", pity))
+            .unwrap_or_else(|| "-".to_string());
+        shapes.push(text(
+            x,
+            line_y,
+            16.0,
+            WEAK_TEXT_COLOR,
+            t!("export.pool_detail", four = data.four_count, average = average),
+        ));
+        for line in lines {
+            line_y += 22.0;
+            shapes.push(text(x, line_y, 15.0, STAR_COLORS[2], line));
+        }
+
+        y += card_height + 16.0;
+    }
+
+    if statistics.is_empty() {
+        shapes.push(text(PADDING, y + 20.0, 16.0, WEAK_TEXT_COLOR, t!("label.no_activity")));
+        y += 40.0;
+    }
+
+    let height = y + PADDING;
+    shapes.insert(0, Shape::Rect { x: 0.0, y: 0.0, width: WIDTH, height, radius: 0.0, color: BACKGROUND });
+    SummaryCard { width: WIDTH, height, shapes }
+}
+
+fn svg_color(color: [u8; 4]) -> String {
+    format!(
+        r##"fill="#{:02x}{:02x}{:02x}" fill-opacity="{:.3}""##,
+        color[0],
+        color[1],
+        color[2],
+        color[3] as f32 / 255.0
+    )
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// 扇形上的点，角度从正上方开始按顺时针计算
+fn sector_point(cx: f32, cy: f32, radius: f32, angle: f32) -> (f32, f32) {
+    (cx + radius * angle.sin(), cy - radius * angle.cos())
+}
+
+/// 以 SVG 格式渲染摘要卡片
+pub(crate) fn render_svg(card: &SummaryCard) -> String {
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+        card.width, card.height
+    );
+    svg.push('\n');
+
+    for shape in card.shapes.iter() {
+        let _ = match shape {
+            Shape::Rect { x, y, width, height, radius, color } => writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" {}/>"#,
+                x, y, width, height, radius, svg_color(*color)
+            ),
+            Shape::Sector { cx, cy, radius, start, end, color } if end - start >= TAU - f32::EPSILON => writeln!(
+                svg,
+                r#"<circle cx="{}" cy="{}" r="{}" {}/>"#,
+                cx, cy, radius, svg_color(*color)
+            ),
+            Shape::Sector { cx, cy, radius, start, end, color } => {
+                let (x1, y1) = sector_point(*cx, *cy, *radius, *start);
+                let (x2, y2) = sector_point(*cx, *cy, *radius, *end);
+                let large_arc = if end - start > TAU / 2.0 { 1 } else { 0 };
+                writeln!(
+                    svg,
+                    r#"<path d="M {} {} L {:.2} {:.2} A {} {} 0 {} 1 {:.2} {:.2} Z" {}/>"#,
+                    cx, cy, x1, y1, radius, radius, large_arc, x2, y2, svg_color(*color)
+                )
+            }
+            Shape::Text { x, y, size, color, bold, text } => writeln!(
+                svg,
+                r#"<text x="{}" y="{}" font-size="{}" font-family="SmileySans, sans-serif"{} {}>{}</text>"#,
+                x,
+                y,
+                size,
+                if *bold { r#" font-weight="bold""# } else { "" },
+                svg_color(*color),
+                escape_xml(text)
+            ),
+        };
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// 加载渲染 PNG 使用的字体，内置字体缺少的字形依次从后备字体中查找
+pub(crate) fn load_fonts(fallback_paths: &[&str]) -> Vec<FontArc> {
+    let mut fonts = vec![];
+    match FontArc::try_from_slice(BUNDLED_FONT) {
+        Ok(font) => fonts.push(font),
+        Err(err) => warn!("内置字体加载失败：{}", err),
+    }
+    for path in fallback_paths {
+        // 字体集合文件只使用其中的第一个字体
+        if let Ok(data) = fs::read(path)
+            && let Ok(font) = FontArc::try_from_vec(data)
+        {
+            fonts.push(font);
+        }
+    }
+    fonts
+}
+
+// 按覆盖率将颜色混合到像素上
+fn blend(image: &mut RgbaImage, x: i32, y: i32, color: [u8; 4], coverage: f32) {
+    if x < 0 || y < 0 || x >= image.width() as i32 || y >= image.height() as i32 {
+        return;
+    }
+    let alpha = color[3] as f32 / 255.0 * coverage.clamp(0.0, 1.0);
+    let pixel = image.get_pixel_mut(x as u32, y as u32);
+    for channel in 0..3 {
+        pixel[channel] = (color[channel] as f32 * alpha + pixel[channel] as f32 * (1.0 - alpha)).round() as u8;
+    }
+    pixel[3] = pixel[3].max((alpha * 255.0).round() as u8);
+}
+
+// 用 4x4 采样计算像素被图形覆盖的比例，用于抗锯齿
+fn coverage(x: i32, y: i32, contains: impl Fn(f32, f32) -> bool) -> f32 {
+    const SAMPLES: i32 = 4;
+    let mut hits = 0;
+    for sx in 0..SAMPLES {
+        for sy in 0..SAMPLES {
+            let px = x as f32 + (sx as f32 + 0.5) / SAMPLES as f32;
+            let py = y as f32 + (sy as f32 + 0.5) / SAMPLES as f32;
+            if contains(px, py) {
+                hits += 1;
+            }
+        }
+    }
+    hits as f32 / (SAMPLES * SAMPLES) as f32
+}
+
+fn fill_shape(image: &mut RgbaImage, bounds: (f32, f32, f32, f32), color: [u8; 4], contains: impl Fn(f32, f32) -> bool) {
+    let (min_x, min_y, max_x, max_y) = bounds;
+    for y in min_y.floor() as i32..max_y.ceil() as i32 {
+        for x in min_x.floor() as i32..max_x.ceil() as i32 {
+            let coverage = coverage(x, y, &contains);
+            if coverage > 0.0 {
+                blend(image, x, y, color, coverage);
+            }
+        }
+    }
+}
+
+fn draw_text(image: &mut RgbaImage, fonts: &[FontArc], x: f32, y: f32, size: f32, color: [u8; 4], text: &str) {
+    let Some(default_font) = fonts.first() else {
+        return;
+    };
+    let mut caret = x;
+    for c in text.chars() {
+        let font = fonts.iter().find(|font| font.glyph_id(c).0 != 0).unwrap_or(default_font);
+        let scaled = font.as_scaled(PxScale::from(size));
+        let mut glyph = scaled.scaled_glyph(c);
+        glyph.position = point(caret, y);
+        caret += scaled.h_advance(glyph.id);
+
+        if let Some(outlined) = font.outline_glyph(glyph) {
+            let bounds = outlined.px_bounds();
+            outlined.draw(|gx, gy, coverage| {
+                blend(image, bounds.min.x as i32 + gx as i32, bounds.min.y as i32 + gy as i32, color, coverage);
+            });
+        }
+    }
+}
+
+/// 以 PNG 格式渲染摘要卡片，不依赖显卡及窗口
+pub(crate) fn render_png(card: &SummaryCard, fonts: &[FontArc]) -> RgbaImage {
+    let mut image = RgbaImage::from_pixel(card.width.ceil() as u32, card.height.ceil() as u32, Rgba([0, 0, 0, 0]));
+
+    for shape in card.shapes.iter() {
+        match shape {
+            Shape::Rect { x, y, width, height, radius, color } => {
+                let (x, y, width, height, radius) = (*x, *y, *width, *height, *radius);
+                fill_shape(&mut image, (x, y, x + width, y + height), *color, |px, py| {
+                    if px < x || px > x + width || py < y || py > y + height {
+                        return false;
+                    }
+                    // 圆角处按到圆角圆心的距离判断
+                    let dx = (x + radius - px).max(px - (x + width - radius)).max(0.0);
+                    let dy = (y + radius - py).max(py - (y + height - radius)).max(0.0);
+                    dx.hypot(dy) <= radius
+                });
+            }
+            Shape::Sector { cx, cy, radius, start, end, color } => {
+                let (cx, cy, radius, start, end) = (*cx, *cy, *radius, *start, *end);
+                fill_shape(&mut image, (cx - radius, cy - radius, cx + radius, cy + radius), *color, |px, py| {
+                    let (dx, dy) = (px - cx, py - cy);
+                    let mut angle = dx.atan2(-dy);
+                    if angle < 0.0 {
+                        angle += TAU;
+                    }
+                    dx.hypot(dy) <= radius && angle >= start && angle <= end
+                });
+            }
+            Shape::Text { x, y, size, color, bold, text } => {
+                draw_text(&mut image, fonts, *x, *y, *size, *color, text);
+                // 字体没有粗体，错开绘制一次加粗
+                if *bold {
+                    draw_text(&mut image, fonts, *x + 0.7, *y, *size, *color, text);
+                }
+            }
+        }
+    }
+
+    image
+}
+
+/// 将摘要卡片或报告导出到文件
+pub(crate) fn export_summary(
+    player_id: &str,
+    statistics: &GachaStatistics,
+    format: ExportFormat,
+    options: ExportOptions,
+    path: &Path,
+    fallback_fonts: &[&str],
+) -> Result<(), CoreError> {
+    let path_text = path.display().to_string();
+    match format {
+        ExportFormat::Svg => fs::write(path, render_svg(&summary_card(player_id, statistics, options))).map_err(|err| CoreError::io(&path_text, err))?,
+        ExportFormat::Html => {
+            let history = load_saved_gacha_data(player_id)?;
+            fs::write(path, render_html(player_id, statistics, &history, options))
+                .map_err(|err| CoreError::io(&path_text, err))?
+        }
+        ExportFormat::Markdown => fs::write(path, render_markdown(player_id, statistics, options))
+            .map_err(|err| CoreError::io(&path_text, err))?,
+        ExportFormat::Png => render_png(&summary_card(player_id, statistics, options), &load_fonts(fallback_fonts))
+            .save_with_format(path, image::ImageFormat::Png)
+            .map_err(|err| CoreError::ExportFailed { path: path_text.clone(), detail: err.to_string() })?,
+    }
+
+    info!("摘要已导出：{}", path_text);
+    Ok(())
+}
+
+#[test]
+fn mask_uid_test() {
+    assert_eq!(mask_uid("100123456"), "10*****56");
+    assert_eq!(mask_uid("1234"), "****");
+    assert_eq!(mask_uid(""), "");
+}
+
+#[test]
+fn summary_card_test() {
+    use crate::core::statistics::{GachaStatisticsData, GachaStatisticsDataItem};
+
+    let item = GachaStatisticsDataItem { name: "<维里奈>".to_string(), count: 66, ..Default::default() };
+    let statistics = GachaStatistics::from([(
+        1,
+        GachaStatisticsData {
+            card_pool_type: 1,
+            total: 100,
+            five_count: 1,
+            four_count: 9,
+            three_count: 90,
+            pull_count: 34,
+            detail: vec![item],
+            ..Default::default()
+        },
+    )]);
+
+    let card = summary_card("100123456", &statistics, ExportOptions { mask_uid: true, ..Default::default() });
+    let svg = render_svg(&card);
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains("10*****56"));
+    assert!(!svg.contains("100123456"));
+    assert!(svg.contains("&lt;维里奈&gt;[66]"));
+    assert_eq!(svg.matches("<path").count(), 3);
+
+    let image = render_png(&card, &load_fonts(&[]));
+    assert_eq!((image.width(), image.height()), (card.width as u32, card.height.ceil() as u32));
+    assert_eq!(image.get_pixel(2, 2).0, BACKGROUND);
+    // 饼图中心附近有颜色
+    let (cx, cy) = (PADDING + 20.0 + PIE_RADIUS, HEADER_HEIGHT + 40.0);
+    assert_ne!(image.get_pixel(cx as u32, cy as u32).0, CARD_BACKGROUND);
+}
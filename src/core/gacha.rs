@@ -2,12 +2,17 @@ use std::collections::BTreeMap;
 use std::fs;
 use std::fs::OpenOptions;
 use std::io::{Read, Write};
-use anyhow::Error;
-use chrono::Local;
+use std::sync::atomic::{AtomicBool, Ordering};
+use chrono::{DateTime, Local, Utc};
 use serde::{Deserialize, Serialize};
-use tracing::info;
-use crate::core::message::MessageType::Normal;
-use crate::core::util;
+use tracing::{info, warn};
+use crate::core::account::account_region;
+use crate::core::error::CoreError;
+use crate::i18n::t;
+use crate::core::message::Event::FetchProgressed;
+use crate::core::http::RequestConfig;
+use crate::core::region::Region;
+use crate::core::{http, util};
 use crate::view::main_view::UiRepaintSender;
 
 // 接口统一返回值
@@ -15,7 +20,7 @@ use crate::view::main_view::UiRepaintSender;
 struct CommonResult {
     code: i32,
     message: String,
-    data: Vec<GachaData>,
+    data: Vec<GachaRecord>,
 }
 
 /* 具体的抽卡数据
@@ -28,7 +33,34 @@ struct CommonResult {
     "count":1,
     "time":"2024-07-05 07:40:58"
 }
+接口返回的 time 为服务器当地时间，保存时统一转换为 UTC 时间
  */
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct GachaRecord {
+    card_pool_type: String,
+    resource_id: i32,
+    quality_level: i32,
+    resource_type: String,
+    name: String,
+    count: i32,
+    time: String,
+}
+
+impl GachaRecord {
+    fn into_gacha_data(self, region: Region) -> Result<GachaData, CoreError> {
+        Ok(GachaData {
+            time: region.parse_time(&self.time)?,
+            card_pool_type: self.card_pool_type,
+            resource_id: self.resource_id,
+            quality_level: self.quality_level,
+            resource_type: self.resource_type,
+            name: self.name,
+            count: self.count,
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct GachaData {
@@ -38,11 +70,61 @@ pub(crate) struct GachaData {
     pub(crate) resource_type: String,
     pub(crate) name: String,
     count: i32,
-    time: String,
+    pub(crate) time: DateTime<Utc>,
+}
+
+impl GachaData {
+    // 同一条抽卡记录的判断依据，名称等字段随接口语言变化，不参与比较
+    pub(crate) fn same_record(&self, other: &GachaData) -> bool {
+        self.resource_id == other.resource_id && self.time == other.time
+    }
 }
 
 pub(crate) type SavedGachaData = BTreeMap<i32, Vec<GachaData>>;
 
+// 解析已保存的抽卡数据，兼容旧版本以服务器当地时间保存的数据
+fn parse_saved_gacha_data(buffer: &str, region: Region, file_path: &str) -> Result<SavedGachaData, CoreError> {
+    let err = match serde_json::from_str::<SavedGachaData>(buffer) {
+        Ok(saved_gacha_data) => return Ok(saved_gacha_data),
+        Err(err) => err,
+    };
+
+    let legacy = serde_json::from_str::<BTreeMap<i32, Vec<GachaRecord>>>(buffer)
+        .map_err(|_| CoreError::corrupted(file_path, err))?;
+    info!("转换旧版本抽卡数据的时间，区服：{:?}", region);
+
+    let mut saved_gacha_data = SavedGachaData::new();
+    for (card_pool_type, records) in legacy {
+        let data = records
+            .into_iter()
+            .map(|record| record.into_gacha_data(region))
+            .collect::<Result<Vec<_>, _>>()?;
+        saved_gacha_data.insert(card_pool_type, data);
+    }
+    Ok(saved_gacha_data)
+}
+
+/// 读取用户已保存的抽卡数据，不存在时返回空数据
+pub(crate) fn load_saved_gacha_data(player_id: &str) -> Result<SavedGachaData, CoreError> {
+    // 旧版本数据需要按区服转换时间，无法判断区服时按国服处理
+    let region = account_region(player_id).unwrap_or(Region::China);
+    load_saved_gacha_file(&format!("./data/{}/gacha_data.json", player_id), region)
+}
+
+// 读取指定的抽卡数据文件，不存在时返回空数据
+pub(crate) fn load_saved_gacha_file(file_path: &str, region: Region) -> Result<SavedGachaData, CoreError> {
+    let buffer = match fs::read_to_string(file_path) {
+        Ok(buffer) => buffer,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(SavedGachaData::default()),
+        Err(err) => return Err(CoreError::io(file_path, err)),
+    };
+    if buffer.is_empty() {
+        return Ok(SavedGachaData::default());
+    }
+
+    parse_saved_gacha_data(&buffer, region, file_path)
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct RequestParam {
@@ -50,13 +132,13 @@ pub(crate) struct RequestParam {
     card_pool_id: String,
     // 卡池类型（写代码时范围为 1-11）
     card_pool_type: i32,
-    // 固定为 zh-Hans
+    // 接口返回数据的语言，默认取自抽卡链接的 lang 参数
     language_code: String,
     // 用户 ID
     pub(crate) player_id: String,
     record_id: String,
     // 同 svr_id
-    server_id: String,
+    pub(crate) server_id: String,
 }
 
 impl RequestParam {
@@ -72,9 +154,71 @@ impl RequestParam {
     }
 }
 
-pub(crate) async fn get_gacha_data(player_id: String, server_sender: &UiRepaintSender) -> Result<(String, SavedGachaData), Error> {
+// 获取失败的卡池编号及失败原因
+pub(crate) type FailedPools = Vec<(i32, CoreError)>;
+
+// 当前卡池编号 1-11，提供对未来两个可能的新池子的兼容
+const MAX_CARD_POOL_TYPE: i32 = 13;
+
+// 数据刷新所处阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FetchPhase {
+    ReadingLog,
+    Fetching,
+    Saving,
+}
+
+/// 数据刷新进度
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FetchProgress {
+    // 发起刷新时的用户 ID，新增用户时为空
+    pub(crate) player_id: String,
+    pub(crate) phase: FetchPhase,
+    // 当前卡池编号及卡池总数
+    pub(crate) pool: i32,
+    pub(crate) pool_total: i32,
+    // 本次刷新获取到的新记录数
+    pub(crate) records: usize,
+}
+
+impl FetchProgress {
+    pub(crate) fn new(player_id: String) -> Self {
+        Self {
+            player_id,
+            phase: FetchPhase::ReadingLog,
+            pool: 0,
+            pool_total: MAX_CARD_POOL_TYPE,
+            records: 0,
+        }
+    }
+
+    pub(crate) fn fraction(&self) -> f32 {
+        match self.phase {
+            FetchPhase::ReadingLog => 0.0,
+            FetchPhase::Fetching => (self.pool - 1).max(0) as f32 / self.pool_total.max(1) as f32,
+            FetchPhase::Saving => 1.0,
+        }
+    }
+}
+
+/// 获取抽卡记录并与已保存的数据合并
+///
+/// 每个卡池请求前检查 `cancel`，取消时直接返回 `CoreError::FetchCancelled`，不改动已保存的数据
+pub(crate) async fn get_gacha_data(
+    player_id: String,
+    request_config: &RequestConfig,
+    cancel: &AtomicBool,
+    server_sender: &UiRepaintSender,
+) -> Result<(String, SavedGachaData, FailedPools), CoreError> {
+    let mut progress = FetchProgress::new(player_id.clone());
+    let _ = server_sender.send(FetchProgressed(progress.clone()));
+
     // 从日志文件中获取抽卡记录 API 所需要的请求参数
     let (oversea, mut param) = util::get_param_from_logfile(player_id, server_sender)?;
+    if !request_config.language_code.is_empty() {
+        param.language_code = request_config.language_code.clone();
+    }
+    let region = Region::from_server(oversea, &param.server_id);
 
     let _ = fs::create_dir_all(format!("./data/{}", param.player_id));
     let file_path = format!("./data/{}/gacha_data.json", param.player_id);
@@ -83,14 +227,16 @@ pub(crate) async fn get_gacha_data(player_id: String, server_sender: &UiRepaintS
         .read(true)
         .write(true)
         .create(true)
-        .open(&file_path)?;
+        .truncate(false)
+        .open(&file_path)
+        .map_err(|err| CoreError::io(&file_path, err))?;
 
     let mut buffer = String::new();
-    file.read_to_string(&mut buffer)?;
+    file.read_to_string(&mut buffer).map_err(|err| CoreError::io(&file_path, err))?;
 
     let mut saved_gacha_data;
     if !buffer.is_empty() {
-        saved_gacha_data = serde_json::from_str::<SavedGachaData>(&*buffer)?;
+        saved_gacha_data = parse_saved_gacha_data(&buffer, region, &file_path)?;
     } else {
         saved_gacha_data = SavedGachaData::default();
     }
@@ -104,64 +250,176 @@ pub(crate) async fn get_gacha_data(player_id: String, server_sender: &UiRepaintS
         "https://gmserver-api.aki-game2.com/gacha/record/query"
     };
 
-    // 当前卡池编号 1-11，提供对未来两个可能的新池子的兼容
-    for card_pool_type in 1..=13 {
-        let _ = server_sender.send(Normal(format!("正在获取卡池 {} 的数据", card_pool_type)));
+    let limiter = request_config.rate_limiter()
+        .map_err(|err| CoreError::Config(err.to_string()))?;
+    let mut failed_pools = FailedPools::new();
+    let mut success_count = 0;
+
+    for card_pool_type in 1..=MAX_CARD_POOL_TYPE {
+        if cancel.load(Ordering::Relaxed) {
+            info!("数据刷新已取消");
+            return Err(CoreError::FetchCancelled);
+        }
+
+        progress.phase = FetchPhase::Fetching;
+        progress.pool = card_pool_type;
+        let _ = server_sender.send(FetchProgressed(progress.clone()));
         param.card_pool_type = card_pool_type;
 
-        let result = reqwest::Client::new()
-            .post(url)
-            .json(&param)
-            .send()
-            .await;
-
-        match result {
-            Ok(res) => {
-                let body = res.json::<CommonResult>().await?;
-                if body.code != 0 {
-                    // 接口请求失败，可能是请求参数变化，删除 url 缓存，下次重新获取
-                    let _ = fs::remove_file(format!("./data/{}/url_cache.txt", param.player_id));
-                    return Err(Error::msg("抽卡链接可能已经失效，请打开抽卡页面后重新获取。"));
-                }
-
-                let mut default = vec![];
-                let saved_gacha_data_by_type = saved_gacha_data.get_mut(&card_pool_type)
-                    .unwrap_or(&mut default);
-
-                let mut gacha_data_by_type = vec![];
-                for gacha_data in body.data {
-                    if saved_gacha_data_by_type.contains(&gacha_data) {
-                        info!("卡池 {} 已经到达上次记录位置，停止记录", card_pool_type);
-                        break;
-                    }
-
-                    // 卡池顺序为时间倒序，此处调整为顺序
-                    gacha_data_by_type.insert(0, gacha_data.clone());
-                }
-
-                // 在旧数据后追加新数据
-                let mut saved_gacha_data_by_type = saved_gacha_data_by_type.clone();
-                saved_gacha_data_by_type.append(&mut gacha_data_by_type);
-                // 保存
-                saved_gacha_data.insert(card_pool_type, saved_gacha_data_by_type);
+        let result = match http::send_with_retry(|| http::client().post(url).json(&param), request_config, &limiter, cancel).await {
+            Ok(res) => res.json::<CommonResult>().await.map_err(CoreError::Network),
+            Err(err) => Err(err),
+        };
+
+        let body = match result {
+            Ok(body) => body,
+            Err(CoreError::FetchCancelled) => {
+                info!("数据刷新已取消");
+                return Err(CoreError::FetchCancelled);
+            }
+            Err(err) => {
+                // 单个卡池失败不影响其他卡池
+                warn!("卡池 {} 获取失败：{}", card_pool_type, err);
+                failed_pools.push((card_pool_type, err));
+                continue;
+            }
+        };
+
+        if body.code != 0 {
+            // 接口请求失败，可能是请求参数变化，删除 url 缓存，下次重新获取
+            let _ = fs::remove_file(format!("./data/{}/url_cache.txt", param.player_id));
+            if success_count == 0 {
+                return Err(CoreError::UrlExpired { code: body.code, message: body.message });
+            }
+
+            // 链接中途失效，剩余卡池均无法获取
+            for card_pool_type in card_pool_type..=MAX_CARD_POOL_TYPE {
+                failed_pools.push((card_pool_type, CoreError::UrlExpired { code: body.code, message: body.message.clone() }));
             }
+            break;
+        }
+
+        let mut default = vec![];
+        let saved_gacha_data_by_type = saved_gacha_data.get_mut(&card_pool_type)
+            .unwrap_or(&mut default);
+
+        // 转换记录时间，存在无法解析的记录时放弃该卡池，避免漏记
+        let records = match body.data
+            .into_iter()
+            .map(|record| record.into_gacha_data(region))
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(records) => records,
             Err(err) => {
-                return Err(Error::msg(format!("网络连接异常：{}", err)));
+                warn!("卡池 {} 的记录时间无法解析：{}", card_pool_type, err);
+                failed_pools.push((card_pool_type, err));
+                continue;
+            }
+        };
+
+        let mut gacha_data_by_type = vec![];
+        for gacha_data in records {
+            if saved_gacha_data_by_type.iter().any(|saved| saved.same_record(&gacha_data)) {
+                info!("卡池 {} 已经到达上次记录位置，停止记录", card_pool_type);
+                break;
             }
+
+            // 卡池顺序为时间倒序，此处调整为顺序
+            gacha_data_by_type.insert(0, gacha_data.clone());
         }
+
+        progress.records += gacha_data_by_type.len();
+
+        // 在旧数据后追加新数据
+        let mut saved_gacha_data_by_type = saved_gacha_data_by_type.clone();
+        saved_gacha_data_by_type.append(&mut gacha_data_by_type);
+        // 保存
+        saved_gacha_data.insert(card_pool_type, saved_gacha_data_by_type);
+        success_count += 1;
+    }
+
+    // 所有卡池均获取失败，不改动已保存的数据
+    if success_count == 0 && let Some((_, err)) = failed_pools.drain(..).next() {
+        return Err(err);
+    }
+
+    // 写入前最后一次检查，保证取消后数据文件保持原样
+    if cancel.load(Ordering::Relaxed) {
+        info!("数据刷新已取消");
+        return Err(CoreError::FetchCancelled);
     }
+    progress.phase = FetchPhase::Saving;
+    let _ = server_sender.send(FetchProgressed(progress));
 
-    let _ = fs::create_dir_all(format!("./data/{}/backup", param.player_id));
+    let backup_dir = format!("./data/{}/backup", param.player_id);
+    let _ = fs::create_dir_all(&backup_dir);
     // 刷新数据前备份数据
-    fs::copy(&file_path, format!("./data/{}/backup/gacha_data.json.{}.backup",
-                                 param.player_id, Local::now().format("%Y-%m-%d-%H-%M-%S-%6f")))?;
+    fs::copy(&file_path, format!("{}/gacha_data.json.{}.backup",
+                                 backup_dir, Local::now().format("%Y-%m-%d-%H-%M-%S-%6f")))
+        .map_err(|err| CoreError::io(&file_path, err))?;
 
     let mut file = OpenOptions::new()
         .write(true)
         .truncate(true)
-        .open(file_path)?;
+        .open(&file_path)
+        .map_err(|err| CoreError::io(&file_path, err))?;
+
+    let data = serde_json::to_vec(&saved_gacha_data)
+        .map_err(|err| CoreError::Internal(t!("error.detail.serialize_gacha", detail = err)))?;
+    file.write_all(&data).map_err(|err| CoreError::io(&file_path, err))?;
+
+    Ok((param.player_id, saved_gacha_data, failed_pools))
+}
+
+#[test]
+fn same_record_test() {
+    let zh = GachaData {
+        resource_id: 21010043,
+        name: "远行者长刃·辟路".to_string(),
+        time: Region::China.parse_time("2024-07-05 07:40:58").unwrap(),
+        ..Default::default()
+    };
+    let en = GachaData {
+        name: "Broadblade of Voyager".to_string(),
+        ..zh.clone()
+    };
+    let later = GachaData {
+        time: Region::China.parse_time("2024-07-05 07:41:30").unwrap(),
+        ..zh.clone()
+    };
+
+    assert!(zh.same_record(&en));
+    assert!(!zh.same_record(&later));
+}
+
+#[test]
+fn parse_saved_gacha_data_test() {
+    // 旧版本以服务器当地时间保存
+    let legacy = r#"{"1":[{"cardPoolType":"角色精准调谐","resourceId":21010043,"qualityLevel":3,"resourceType":"武器","name":"远行者长刃·辟路","count":1,"time":"2024-07-05 07:40:58"}]}"#;
+    let saved_gacha_data = parse_saved_gacha_data(legacy, Region::America, "gacha_data.json").unwrap();
+    assert_eq!(saved_gacha_data[&1][0].time.to_rfc3339(), "2024-07-05T12:40:58+00:00");
 
-    let _ = &file.write_all(&*serde_json::to_vec(&saved_gacha_data)?)?;
+    // 新版本保存的 UTC 时间原样读取
+    let current = String::from_utf8(serde_json::to_vec(&saved_gacha_data).unwrap()).unwrap();
+    assert_eq!(parse_saved_gacha_data(&current, Region::China, "gacha_data.json").unwrap(), saved_gacha_data);
 
-    Ok((param.player_id, saved_gacha_data))
-}
\ No newline at end of file
+    assert!(matches!(
+        parse_saved_gacha_data("[", Region::China, "gacha_data.json"),
+        Err(CoreError::StorageCorrupted { .. })
+    ));
+}
+
+#[test]
+fn fetch_progress_test() {
+    let mut progress = FetchProgress::new(String::new());
+    assert_eq!(progress.fraction(), 0.0);
+
+    progress.phase = FetchPhase::Fetching;
+    progress.pool = 1;
+    assert_eq!(progress.fraction(), 0.0);
+    progress.pool = MAX_CARD_POOL_TYPE;
+    assert!(progress.fraction() < 1.0);
+
+    progress.phase = FetchPhase::Saving;
+    assert_eq!(progress.fraction(), 1.0);
+}
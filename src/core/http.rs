@@ -0,0 +1,279 @@
+use std::io::ErrorKind;
+use std::sync::{LazyLock, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use ratelimit::{Ratelimiter, TryWaitError};
+use reqwest::{Client, Proxy, RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use crate::core::error::CoreError;
+use crate::i18n::t;
+
+// 全局共享的 HTTP 客户端，复用连接池，网络设置变更时重新创建
+static CLIENT: LazyLock<RwLock<Client>> = LazyLock::new(|| {
+    RwLock::new(build_client(&NetworkConfig::default()).unwrap_or_default())
+});
+
+pub(crate) fn client() -> Client {
+    CLIENT.read().map(|client| client.clone()).unwrap_or_default()
+}
+
+// 网络设置，作用于 core 中的所有请求
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub(crate) struct NetworkConfig {
+    // 代理地址，支持 http://、https://、socks5:// 及 socks5h://，为空时不使用代理
+    pub(crate) proxy: String,
+    // 连接及读取超时时间（秒）
+    pub(crate) timeout_secs: u64,
+    // 启动时自动检查更新
+    pub(crate) auto_check_update: bool,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            proxy: String::new(),
+            timeout_secs: 30,
+            auto_check_update: true,
+        }
+    }
+}
+
+/// 按网络设置重新创建全局 HTTP 客户端
+pub(crate) fn configure(config: &NetworkConfig) -> Result<(), CoreError> {
+    let client = build_client(config)?;
+    if let Ok(mut current) = CLIENT.write() {
+        *current = client;
+    }
+    Ok(())
+}
+
+fn build_client(config: &NetworkConfig) -> Result<Client, CoreError> {
+    let timeout = Duration::from_secs(config.timeout_secs.max(1));
+    // 下载更新包耗时较长，不限制请求总时长，只限制连接及每次读取的等待时间
+    let mut builder = Client::builder()
+        .connect_timeout(timeout)
+        .read_timeout(timeout);
+
+    let proxy = config.proxy.trim();
+    if !proxy.is_empty() {
+        let proxy = Proxy::all(proxy).map_err(|err| CoreError::Config(t!("error.detail.invalid_proxy", detail = err)))?;
+        builder = builder.proxy(proxy);
+    }
+
+    builder.build().map_err(|err| CoreError::Config(err.to_string()))
+}
+
+// 抽卡记录接口请求配置
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub(crate) struct RequestConfig {
+    // 每秒最多请求次数，0 为不限制
+    pub(crate) requests_per_second: u64,
+    // 遇到临时性错误时的最大重试次数
+    pub(crate) max_retries: u32,
+    // 首次重试前的等待时间（毫秒），之后每次翻倍
+    pub(crate) retry_base_delay_ms: u64,
+    // 请求数据使用的语言（如 zh-Hans、en、ja），为空时使用抽卡链接中的语言
+    pub(crate) language_code: String,
+}
+
+impl Default for RequestConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_second: 2,
+            max_retries: 3,
+            retry_base_delay_ms: 500,
+            language_code: String::new(),
+        }
+    }
+}
+
+impl RequestConfig {
+    pub(crate) fn rate_limiter(&self) -> Result<Ratelimiter, ratelimit::Error> {
+        Ratelimiter::builder(self.requests_per_second)
+            // 第一个请求无需等待
+            .initial_available(1)
+            .build()
+    }
+
+    // 第 retry 次重试前的等待时间，最长 30 秒
+    pub(crate) fn backoff(&self, retry: u32) -> Duration {
+        let delay = self.retry_base_delay_ms.saturating_mul(1 << retry.min(16));
+        Duration::from_millis(delay.min(30_000))
+    }
+}
+
+// 等待期间检查取消标记的间隔
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// 等待指定时间，期间被取消时返回 FetchCancelled
+async fn sleep_or_cancel(duration: Duration, cancel: &AtomicBool) -> Result<(), CoreError> {
+    let deadline = Instant::now() + duration;
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(CoreError::FetchCancelled);
+        }
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Ok(());
+        }
+        tokio::time::sleep(remaining.min(CANCEL_POLL_INTERVAL)).await;
+    }
+}
+
+// 直到被取消才返回
+async fn cancelled(cancel: &AtomicBool) {
+    while !cancel.load(Ordering::Relaxed) {
+        tokio::time::sleep(CANCEL_POLL_INTERVAL).await;
+    }
+}
+
+/// 发送请求，遇到临时性错误（5xx、超时、连接被重置等）时按指数退避重试
+///
+/// 等待请求令牌、等待响应及重试前的退避期间被取消时立即返回 FetchCancelled
+pub(crate) async fn send_with_retry<F>(
+    request: F,
+    config: &RequestConfig,
+    limiter: &Ratelimiter,
+    cancel: &AtomicBool,
+) -> Result<Response, CoreError>
+where
+    F: Fn() -> RequestBuilder,
+{
+    let mut retry = 0;
+    loop {
+        // 等待请求令牌
+        while let Err(TryWaitError::Insufficient(wait)) = limiter.try_wait() {
+            sleep_or_cancel(wait, cancel).await?;
+        }
+
+        let result = tokio::select! {
+            result = request().send() => result.and_then(Response::error_for_status),
+            _ = cancelled(cancel) => return Err(CoreError::FetchCancelled),
+        };
+
+        match result {
+            Ok(response) => return Ok(response),
+            Err(err) if retry < config.max_retries && is_transient(&err) => {
+                let delay = config.backoff(retry);
+                retry += 1;
+                warn!("请求失败，{}ms 后进行第 {} 次重试：{}", delay.as_millis(), retry, err);
+                sleep_or_cancel(delay, cancel).await?;
+            }
+            Err(err) => return Err(CoreError::Network(err)),
+        }
+    }
+}
+
+// 判断是否为可重试的临时性错误
+fn is_transient(err: &reqwest::Error) -> bool {
+    if err.is_timeout() || err.is_connect() {
+        return true;
+    }
+
+    if let Some(status) = err.status() {
+        return status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS;
+    }
+
+    // 连接被重置等底层 IO 错误
+    let mut source = std::error::Error::source(err);
+    while let Some(inner) = source {
+        if let Some(io_err) = inner.downcast_ref::<std::io::Error>() {
+            return matches!(
+                io_err.kind(),
+                ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::BrokenPipe
+                    | ErrorKind::UnexpectedEof
+                    | ErrorKind::TimedOut
+            );
+        }
+        source = inner.source();
+    }
+
+    false
+}
+
+#[test]
+fn build_client_test() {
+    let config = NetworkConfig {
+        proxy: "socks5://127.0.0.1:1080".to_string(),
+        ..Default::default()
+    };
+    assert!(build_client(&config).is_ok());
+
+    let config = NetworkConfig {
+        proxy: "not a proxy".to_string(),
+        ..Default::default()
+    };
+    assert!(matches!(build_client(&config), Err(CoreError::Config(_))));
+}
+
+#[test]
+fn backoff_test() {
+    let config = RequestConfig::default();
+    assert_eq!(config.backoff(0), Duration::from_millis(500));
+    assert_eq!(config.backoff(1), Duration::from_millis(1000));
+    assert_eq!(config.backoff(2), Duration::from_millis(2000));
+    assert_eq!(config.backoff(20), Duration::from_secs(30));
+}
+
+#[tokio::test]
+async fn send_with_retry_test() {
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicUsize;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    // 本地模拟服务：前两次返回 503，之后返回 200；/unavailable 始终返回 503
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let hits = Arc::new(AtomicUsize::new(0));
+    let server_hits = Arc::clone(&hits);
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut buffer = [0; 1024];
+            let len = stream.read(&mut buffer).await.unwrap_or(0);
+            let unavailable = String::from_utf8_lossy(&buffer[..len]).starts_with("GET /unavailable ");
+            let status = if unavailable || server_hits.fetch_add(1, Ordering::SeqCst) < 2 {
+                "503 Service Unavailable"
+            } else {
+                "200 OK"
+            };
+            let response = format!("HTTP/1.1 {}\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok", status);
+            let _ = stream.write_all(response.as_bytes()).await;
+        }
+    });
+
+    let config = RequestConfig {
+        requests_per_second: 0,
+        max_retries: 3,
+        retry_base_delay_ms: 1,
+        ..Default::default()
+    };
+    let limiter = config.rate_limiter().unwrap();
+    let url = format!("http://{}/", addr);
+    let response = send_with_retry(|| client().get(&url), &config, &limiter, &AtomicBool::new(false)).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+    // 重试前的退避期间取消，无需等待退避结束
+    let config = RequestConfig {
+        retry_base_delay_ms: 30_000,
+        ..config
+    };
+    let cancel = Arc::new(AtomicBool::new(false));
+    let cancel_later = Arc::clone(&cancel);
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        cancel_later.store(true, Ordering::Relaxed);
+    });
+    let url = format!("http://{}/unavailable", addr);
+    let started = Instant::now();
+    let result = send_with_retry(|| client().get(&url), &config, &limiter, &cancel).await;
+    assert!(matches!(result, Err(CoreError::FetchCancelled)));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
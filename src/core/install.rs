@@ -0,0 +1,418 @@
+use std::fs;
+use std::fs::File;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use flate2::read::GzDecoder;
+use tar::Archive;
+use tracing::{info, warn};
+use zip::ZipArchive;
+use crate::core::error::CoreError;
+use crate::i18n::t;
+
+// 新版本程序以该参数启动时只输出版本号并退出，用于安装后的自检
+pub(crate) const SMOKE_CHECK_ARG: &str = "--smoke-check";
+
+// 安装过程中使用的临时目录，位于程序目录内，保证与程序在同一文件系统中
+const STAGING_DIR: &str = ".update-staging";
+const BACKUP_DIR: &str = ".update-backup";
+// 用户数据目录，安装时不做任何改动
+const DATA_DIR: &str = "data";
+
+const SMOKE_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 安装更新包并返回新程序的路径
+///
+/// 先将更新包解压到暂存目录，再逐个替换程序目录中的文件，被替换的文件移动到备份目录；
+/// 新程序自检失败时还原全部文件
+pub(crate) fn install_update(
+    package: &Path,
+    install_dir: &Path,
+    exe_name: &str,
+    version: &str,
+) -> Result<PathBuf, CoreError> {
+    let staging_dir = install_dir.join(STAGING_DIR);
+    let backup_dir = install_dir.join(BACKUP_DIR);
+    remove_dir(&staging_dir)?;
+    remove_dir(&backup_dir)?;
+
+    unpack(package, &staging_dir, exe_name)?;
+
+    // 压缩包内可能多包了一层目录，以程序所在目录为准
+    let staged_root = find_file(&staging_dir, exe_name)
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        .ok_or_else(|| CoreError::UpdateArchiveInvalid {
+            path: package.display().to_string(),
+            detail: t!("error.detail.exe_missing", exe = exe_name),
+        })?;
+
+    let mut swapped = vec![];
+    let result = swap_files(&staged_root, &staged_root, install_dir, &backup_dir, &mut swapped)
+        .and_then(|_| smoke_check(&install_dir.join(exe_name), version));
+
+    if let Err(err) = result {
+        warn!("更新安装失败，还原旧版本：{}", err);
+        rollback(&swapped);
+        let _ = remove_dir(&staging_dir);
+        return Err(err);
+    }
+
+    let _ = remove_dir(&staging_dir);
+    info!("更新安装完成，替换了 {} 个文件", swapped.len());
+    Ok(install_dir.join(exe_name))
+}
+
+/// 清理上次安装留下的备份，运行中的旧程序在 Windows 下无法在安装时删除
+pub(crate) fn cleanup_previous_install(install_dir: &Path) {
+    for dir in [STAGING_DIR, BACKUP_DIR] {
+        let _ = fs::remove_dir_all(install_dir.join(dir));
+    }
+}
+
+/// 启动新程序，调用方随后应退出当前程序
+pub(crate) fn relaunch(exe: &Path) -> Result<(), CoreError> {
+    Command::new(exe)
+        .spawn()
+        .map(|_| ())
+        .map_err(|err| CoreError::io(exe.display().to_string(), err))
+}
+
+/// 更新包格式，由文件名判断
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum PackageFormat {
+    Zip,
+    TarGz,
+    // 未压缩的 Windows 程序
+    Exe,
+}
+
+impl PackageFormat {
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        if name.ends_with(".zip") {
+            Some(PackageFormat::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(PackageFormat::TarGz)
+        } else if name.ends_with(".exe") {
+            Some(PackageFormat::Exe)
+        } else {
+            None
+        }
+    }
+}
+
+// 按格式解压更新包，不支持的格式不做任何改动
+fn unpack(package: &Path, staging_dir: &Path, exe_name: &str) -> Result<(), CoreError> {
+    let package_path = package.display().to_string();
+    let invalid = |detail: String| CoreError::UpdateArchiveInvalid {
+        path: package_path.clone(),
+        detail,
+    };
+    let name = package.file_name().unwrap_or_default().to_string_lossy();
+    let format = PackageFormat::from_name(&name)
+        .ok_or_else(|| invalid(t!("error.detail.unsupported_package", name = name)))?;
+
+    fs::create_dir_all(staging_dir).map_err(|err| CoreError::io(staging_dir.display().to_string(), err))?;
+    match format {
+        PackageFormat::Zip => unpack_zip(package, staging_dir, exe_name),
+        PackageFormat::TarGz => unpack_tar_gz(package, staging_dir, exe_name),
+        PackageFormat::Exe => {
+            let target = staging_dir.join(exe_name);
+            fs::copy(package, &target).map_err(|err| CoreError::io(&package_path, err))?;
+            set_executable(&target)
+        }
+    }
+}
+
+fn unpack_zip(package: &Path, staging_dir: &Path, exe_name: &str) -> Result<(), CoreError> {
+    let package_path = package.display().to_string();
+    let file = File::open(package).map_err(|err| CoreError::io(&package_path, err))?;
+    let invalid = |detail: String| CoreError::UpdateArchiveInvalid {
+        path: package_path.clone(),
+        detail,
+    };
+    let mut archive = ZipArchive::new(file).map_err(|err| invalid(err.to_string()))?;
+
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(|err| invalid(err.to_string()))?;
+        // 拒绝指向解压目录之外的路径
+        let relative = entry
+            .enclosed_name()
+            .ok_or_else(|| invalid(t!("error.detail.illegal_path", path = entry.name())))?;
+        let target = staging_dir.join(&relative);
+        let target_path = target.display().to_string();
+
+        if entry.is_dir() {
+            fs::create_dir_all(&target).map_err(|err| CoreError::io(&target_path, err))?;
+            continue;
+        }
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|err| CoreError::io(&target_path, err))?;
+        }
+        let mut output = File::create(&target).map_err(|err| CoreError::io(&target_path, err))?;
+        std::io::copy(&mut entry, &mut output).map_err(|err| CoreError::io(&target_path, err))?;
+
+        if target.file_name().is_some_and(|name| name == exe_name) {
+            set_executable(&target)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn unpack_tar_gz(package: &Path, staging_dir: &Path, exe_name: &str) -> Result<(), CoreError> {
+    let package_path = package.display().to_string();
+    let file = File::open(package).map_err(|err| CoreError::io(&package_path, err))?;
+    let invalid = |detail: String| CoreError::UpdateArchiveInvalid {
+        path: package_path.clone(),
+        detail,
+    };
+    let mut archive = Archive::new(GzDecoder::new(file));
+
+    for entry in archive.entries().map_err(|err| invalid(err.to_string()))? {
+        let mut entry = entry.map_err(|err| invalid(err.to_string()))?;
+        let relative = entry.path().map_err(|err| invalid(err.to_string()))?.into_owned();
+        // 拒绝指向解压目录之外的路径
+        let unpacked = entry.unpack_in(staging_dir).map_err(|err| invalid(err.to_string()))?;
+        if !unpacked {
+            return Err(invalid(t!("error.detail.illegal_path", path = relative.display())));
+        }
+
+        if relative.file_name().is_some_and(|name| name == exe_name) {
+            set_executable(&staging_dir.join(&relative))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn find_file(dir: &Path, name: &str) -> Option<PathBuf> {
+    let mut entries: Vec<_> = fs::read_dir(dir).ok()?.flatten().map(|entry| entry.path()).collect();
+    // 优先使用浅层目录中的文件
+    entries.sort_by_key(|path| path.is_dir());
+
+    entries.into_iter().find_map(|path| {
+        if path.is_dir() {
+            find_file(&path, name)
+        } else if path.file_name().is_some_and(|file_name| file_name == name) {
+            Some(path)
+        } else {
+            None
+        }
+    })
+}
+
+// 已替换的文件及其备份，新增的文件没有备份
+struct Swapped {
+    target: PathBuf,
+    backup: Option<PathBuf>,
+}
+
+fn swap_files(
+    staged_root: &Path,
+    dir: &Path,
+    install_dir: &Path,
+    backup_dir: &Path,
+    swapped: &mut Vec<Swapped>,
+) -> Result<(), CoreError> {
+    let entries = fs::read_dir(dir).map_err(|err| CoreError::io(dir.display().to_string(), err))?;
+    for entry in entries.flatten() {
+        let source = entry.path();
+        let Ok(relative) = source.strip_prefix(staged_root) else {
+            continue;
+        };
+        // 更新包中的 data 目录不覆盖用户数据
+        if relative.components().next() == Some(Component::Normal(DATA_DIR.as_ref())) {
+            continue;
+        }
+
+        if source.is_dir() {
+            swap_files(staged_root, &source, install_dir, backup_dir, swapped)?;
+            continue;
+        }
+
+        let target = install_dir.join(relative);
+        let target_path = target.display().to_string();
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|err| CoreError::io(&target_path, err))?;
+        }
+
+        // 运行中的程序文件无法覆盖，但可以重命名
+        let backup = if target.exists() {
+            let backup = backup_dir.join(relative);
+            if let Some(parent) = backup.parent() {
+                fs::create_dir_all(parent).map_err(|err| CoreError::io(backup.display().to_string(), err))?;
+            }
+            fs::rename(&target, &backup).map_err(|err| CoreError::io(&target_path, err))?;
+            Some(backup)
+        } else {
+            None
+        };
+        swapped.push(Swapped { target: target.clone(), backup });
+
+        fs::rename(&source, &target).map_err(|err| CoreError::io(&target_path, err))?;
+    }
+
+    Ok(())
+}
+
+fn rollback(swapped: &[Swapped]) {
+    for Swapped { target, backup } in swapped.iter().rev() {
+        let _ = fs::remove_file(target);
+        if let Some(backup) = backup
+            && let Err(err) = fs::rename(backup, target)
+        {
+            warn!("还原文件 {} 失败：{}", target.display(), err);
+        }
+    }
+}
+
+// 以自检参数启动新程序，要求其在限定时间内正常退出并输出目标版本号
+fn smoke_check(exe: &Path, version: &str) -> Result<(), CoreError> {
+    let failed = |detail: String| CoreError::UpdateSmokeCheckFailed { detail };
+
+    let mut child = Command::new(exe)
+        .arg(SMOKE_CHECK_ARG)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| failed(err.to_string()))?;
+
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if started.elapsed() < SMOKE_CHECK_TIMEOUT => thread::sleep(Duration::from_millis(50)),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(failed(t!("error.detail.launch_timeout")));
+            }
+            Err(err) => return Err(failed(err.to_string())),
+        }
+    }
+
+    let output = child.wait_with_output().map_err(|err| failed(err.to_string()))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let reported = stdout.trim();
+    if !output.status.success() {
+        return Err(failed(t!("error.detail.exit_status", status = output.status)));
+    }
+    if reported != version.trim_start_matches(['v', 'V']) {
+        return Err(failed(t!("error.detail.version_mismatch", version = reported)));
+    }
+
+    Ok(())
+}
+
+fn remove_dir(dir: &Path) -> Result<(), CoreError> {
+    match fs::remove_dir_all(dir) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(CoreError::io(dir.display().to_string(), err)),
+        _ => Ok(()),
+    }
+}
+
+fn set_executable(path: &Path) -> Result<(), CoreError> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+            .map_err(|err| CoreError::io(path.display().to_string(), err))?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn install_update_test() {
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    let install_dir = std::env::temp_dir().join(format!("wwgr-install-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&install_dir);
+    fs::create_dir_all(install_dir.join(DATA_DIR)).unwrap();
+    fs::write(install_dir.join(DATA_DIR).join("config.toml"), "dark_mode = true").unwrap();
+    fs::write(install_dir.join("app"), "old").unwrap();
+
+    // 打包一个输出指定版本号的脚本作为新程序
+    let package = |name: &str, version: &str| {
+        let path = install_dir.join(name);
+        let mut writer = zip::ZipWriter::new(File::create(&path).unwrap());
+        let options = SimpleFileOptions::default().unix_permissions(0o755);
+        writer.start_file("release/app", options).unwrap();
+        writer.write_all(format!("#!/bin/sh\necho {}\n", version).as_bytes()).unwrap();
+        writer.start_file("release/README.md", options).unwrap();
+        writer.write_all(b"readme").unwrap();
+        writer.start_file("release/data/config.toml", options).unwrap();
+        writer.write_all(b"overwritten").unwrap();
+        writer.finish().unwrap();
+        path
+    };
+
+    // 自检失败时还原旧版本
+    let broken = package("broken.zip", "0.0.1");
+    let result = install_update(&broken, &install_dir, "app", "v9.0.0");
+    assert!(matches!(result, Err(CoreError::UpdateSmokeCheckFailed { .. })));
+    assert_eq!(fs::read_to_string(install_dir.join("app")).unwrap(), "old");
+    assert!(!install_dir.join("README.md").exists());
+
+    // 安装成功，data 目录保持不变
+    let good = package("good.zip", "9.0.0");
+    let exe = install_update(&good, &install_dir, "app", "v9.0.0").unwrap();
+    assert_eq!(exe, install_dir.join("app"));
+    assert!(fs::read_to_string(&exe).unwrap().contains("echo 9.0.0"));
+    assert_eq!(fs::read_to_string(install_dir.join("README.md")).unwrap(), "readme");
+    assert_eq!(fs::read_to_string(install_dir.join(DATA_DIR).join("config.toml")).unwrap(), "dark_mode = true");
+    assert_eq!(fs::read_to_string(install_dir.join(BACKUP_DIR).join("app")).unwrap(), "old");
+
+    cleanup_previous_install(&install_dir);
+    assert!(!install_dir.join(BACKUP_DIR).exists());
+    let _ = fs::remove_dir_all(&install_dir);
+}
+
+#[cfg(unix)]
+#[test]
+fn unpack_test() {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    let dir = std::env::temp_dir().join(format!("wwgr-unpack-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    // Linux 版本的 tar.gz 更新包
+    let package = dir.join("app-v9.0.0-linux-x86_64.tar.gz");
+    let mut builder = tar::Builder::new(GzEncoder::new(File::create(&package).unwrap(), Compression::default()));
+    let content = b"#!/bin/sh\necho 9.0.0\n";
+    let mut header = tar::Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, "release/app", &content[..]).unwrap();
+    builder.into_inner().unwrap().finish().unwrap();
+
+    let staging_dir = dir.join(STAGING_DIR);
+    unpack(&package, &staging_dir, "app").unwrap();
+    let exe = find_file(&staging_dir, "app").unwrap();
+    assert_eq!(fs::read(&exe).unwrap(), content);
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(fs::metadata(&exe).unwrap().permissions().mode() & 0o777, 0o755);
+    }
+
+    // 不支持的格式不会被当作程序复制
+    let package = dir.join("app-v9.0.0-linux-x86_64.AppImage");
+    fs::write(&package, "image").unwrap();
+    let staging_dir = dir.join("unsupported");
+    assert!(matches!(unpack(&package, &staging_dir, "app"), Err(CoreError::UpdateArchiveInvalid { .. })));
+    assert!(!staging_dir.exists());
+
+    assert_eq!(PackageFormat::from_name("app.TGZ"), Some(PackageFormat::TarGz));
+    assert_eq!(PackageFormat::from_name("app.exe"), Some(PackageFormat::Exe));
+    assert_eq!(PackageFormat::from_name("app.tar.xz"), None);
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -1,13 +1,62 @@
-use crate::core::statistics::GachaStatistics;
-use crate::core::update::Release;
+use std::path::PathBuf;
+use crate::core::account::{AccountAction, AccountInfo};
+use crate::core::error::CoreError;
+use crate::core::export::{ExportFormat, ExportOptions};
+use crate::core::gacha::{FailedPools, FetchProgress};
+use crate::core::http::RequestConfig;
+use crate::core::region::DisplayTimeZone;
+use crate::core::roster::Roster;
+use crate::core::statistics::{AccountComparison, ActivityStatistics, AggregateStatistics, GachaStatistics};
+use crate::core::update::{DownloadProgress, Release, UpdateConfig};
 
-pub(crate) enum MessageType {
-    CheckUpdate,
-    NeedUpdate(Release),
-    DownloadFile(Release, String),
+/// 视图发送给后台服务的指令
+pub(crate) enum Command {
+    CheckUpdate(UpdateConfig),
+    // 更新包及保存目录
+    DownloadFile(Box<Release>, String),
+    CancelDownload,
+    // 更新包路径及目标版本号
+    InstallUpdate(PathBuf, String),
+    // 是否优先读取缓存及用户 ID，用户 ID 为空时从日志中获取新用户
+    UpdateData(bool, String),
+    CancelFetch,
+    UpdateRequestConfig(RequestConfig),
+    LoadAccounts,
+    // 汇总所选用户的统计数据
+    LoadAggregate(Vec<String>),
+    // 对比两个用户的统计数据
+    LoadComparison(String, String),
+    // 按展示时区汇总用户各时间段的抽卡情况
+    LoadActivity(String, DisplayTimeZone),
+    // 按抽卡记录统计用户已获取的角色及武器
+    LoadRoster(String),
+    // 用户 ID 及对其执行的操作
+    UpdateAccount(String, AccountAction),
+    // 用户 ID、导出格式、导出选项及保存路径
+    ExportSummary(String, ExportFormat, ExportOptions, PathBuf),
+    // 取消进行中的任务并等待其结束，完成后通过回执通知视图
+    Shutdown(std::sync::mpsc::Sender<()>),
+}
+
+/// 后台服务发送给视图的事件
+pub(crate) enum Event {
+    NeedUpdate(Box<Release>),
+    DownloadProgressed(DownloadProgress),
+    DownloadFinished(Result<PathBuf, CoreError>),
+    UpdateInstalled(Result<PathBuf, CoreError>),
     Normal(String),
     Warning(String),
+    Error(CoreError),
+    PoolsFailed(FailedPools),
     Gacha((String, GachaStatistics)),
     Player(Vec<String>),
-    UpdateData(bool, String),
-}
\ No newline at end of file
+    // 全部用户的信息，包含已隐藏的用户
+    Accounts(Vec<AccountInfo>),
+    Aggregate(AggregateStatistics),
+    Comparison(AccountComparison),
+    Activity(ActivityStatistics),
+    Roster(Roster),
+    FetchProgressed(FetchProgress),
+    // 用户的刷新任务结束，无论成功与否，与刷新进度中的用户 ID 一致
+    FetchFinished(String),
+}
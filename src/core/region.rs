@@ -0,0 +1,131 @@
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, Offset, Utc};
+use serde::{Deserialize, Serialize};
+use crate::core::error::CoreError;
+
+// 接口返回的时间格式，为服务器当地时间
+const SERVER_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// 游戏区服，不同区服的抽卡记录时间使用各自的时区
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Region {
+    China,
+    America,
+    Europe,
+    Asia,
+    Sea,
+    Hmt,
+}
+
+impl Region {
+    pub(crate) const ALL: [Region; 6] = [
+        Region::China,
+        Region::America,
+        Region::Europe,
+        Region::Asia,
+        Region::Sea,
+        Region::Hmt,
+    ];
+
+    /// 根据抽卡链接的域名及 svr_id 判断区服
+    pub(crate) fn from_server(oversea: bool, server_id: &str) -> Self {
+        if !oversea {
+            return Region::China;
+        }
+
+        match server_id {
+            "591d6af3a3090d8ea00d8f86cf6d7501" => Region::America,
+            "6eb2a235b30d05efd77bedb5cf60999e" => Region::Europe,
+            "86d52186155b148b5c138ceb41be9650" => Region::Asia,
+            "919752ae5ea09c1ced910dd668a63ffb" => Region::Sea,
+            "10cd7254d57e58ae560b15d51e34b4c8" => Region::Hmt,
+            // 未知的国际服按亚服处理
+            _ => Region::Asia,
+        }
+    }
+
+    pub(crate) fn key(&self) -> &'static str {
+        match self {
+            Region::China => "china",
+            Region::America => "america",
+            Region::Europe => "europe",
+            Region::Asia => "asia",
+            Region::Sea => "sea",
+            Region::Hmt => "hmt",
+        }
+    }
+
+    // 服务器时间相对 UTC 的偏移
+    pub(crate) fn utc_offset(&self) -> FixedOffset {
+        let hours = match self {
+            Region::America => -5,
+            Region::Europe => 1,
+            Region::China | Region::Asia | Region::Sea | Region::Hmt => 8,
+        };
+        FixedOffset::east_opt(hours * 3600).unwrap_or_else(|| Utc.fix())
+    }
+
+    /// 将接口返回的服务器当地时间转换为 UTC 时间
+    pub(crate) fn parse_time(&self, time: &str) -> Result<DateTime<Utc>, CoreError> {
+        let naive = NaiveDateTime::parse_from_str(time, SERVER_TIME_FORMAT)
+            .map_err(|_| CoreError::InvalidTime(time.to_string()))?;
+        naive
+            .and_local_timezone(self.utc_offset())
+            .single()
+            .map(|time| time.with_timezone(&Utc))
+            .ok_or_else(|| CoreError::InvalidTime(time.to_string()))
+    }
+}
+
+/// 时间的展示时区
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum DisplayTimeZone {
+    // 系统本地时间
+    #[default]
+    Local,
+    Utc,
+    // 指定区服的服务器时间
+    Server(Region),
+}
+
+impl DisplayTimeZone {
+    pub(crate) fn format(&self, time: &DateTime<Utc>) -> String {
+        const FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+        match self {
+            DisplayTimeZone::Local => time.with_timezone(&Local).format(FORMAT).to_string(),
+            DisplayTimeZone::Utc => time.format(FORMAT).to_string(),
+            DisplayTimeZone::Server(region) => time.with_timezone(&region.utc_offset()).format(FORMAT).to_string(),
+        }
+    }
+
+    // 按展示时区计算所在日期，用于按日期汇总
+    pub(crate) fn date(&self, time: &DateTime<Utc>) -> NaiveDate {
+        match self {
+            DisplayTimeZone::Local => time.with_timezone(&Local).date_naive(),
+            DisplayTimeZone::Utc => time.date_naive(),
+            DisplayTimeZone::Server(region) => time.with_timezone(&region.utc_offset()).date_naive(),
+        }
+    }
+}
+
+#[test]
+fn parse_time_test() {
+    let china = Region::China.parse_time("2024-07-05 07:40:58").unwrap();
+    let america = Region::America.parse_time("2024-07-04 18:40:58").unwrap();
+
+    // 不同区服的同一时刻转换后相等
+    assert_eq!(china, america);
+    assert_eq!(china.to_rfc3339(), "2024-07-04T23:40:58+00:00");
+    assert_eq!(DisplayTimeZone::Server(Region::Europe).format(&china), "2024-07-05 00:40:58");
+    assert!(Region::Asia.parse_time("invalid").is_err());
+}
+
+#[test]
+fn from_server_test() {
+    assert_eq!(Region::from_server(false, "76402e5b20be2c39f095a152090afddc"), Region::China);
+    assert_eq!(Region::from_server(true, "591d6af3a3090d8ea00d8f86cf6d7501"), Region::America);
+    assert_eq!(Region::from_server(true, "6eb2a235b30d05efd77bedb5cf60999e"), Region::Europe);
+    assert_eq!(Region::from_server(true, "86d52186155b148b5c138ceb41be9650"), Region::Asia);
+    assert_eq!(Region::from_server(true, "919752ae5ea09c1ced910dd668a63ffb"), Region::Sea);
+    assert_eq!(Region::from_server(true, "10cd7254d57e58ae560b15d51e34b4c8"), Region::Hmt);
+    assert_eq!(Region::from_server(true, "unknown"), Region::Asia);
+}
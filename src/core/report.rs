@@ -0,0 +1,320 @@
+use std::fmt::Write as _;
+use chrono::Local;
+use crate::core::export::{escape_xml, mask_uid, render_svg, summary_card, ExportOptions};
+use crate::core::gacha::{GachaData, SavedGachaData};
+use crate::core::resource::resource_name;
+use crate::core::statistics::{pity_histogram, GachaStatistics, GachaStatisticsData, GachaStatisticsDataItem, StatisticsSummary};
+use crate::i18n;
+use crate::i18n::{card_pool_name, t};
+use crate::VERSION;
+
+// 出金分布图每组的抽数
+const HISTOGRAM_BIN_WIDTH: i32 = 5;
+const HISTOGRAM_WIDTH: f32 = 640.0;
+const HISTOGRAM_HEIGHT: f32 = 180.0;
+
+// 报告的样式，配色与摘要卡片一致
+const REPORT_STYLE: &str = r#"
+body { margin: 0; padding: 24px; background: #1f2430; color: #e8e8e8; font-family: "SmileySans", "Microsoft YaHei", "PingFang SC", sans-serif; }
+main { max-width: 760px; margin: 0 auto; }
+h1 { margin: 0 0 4px; }
+.weak { color: #a0a8b8; }
+section { background: #2a3140; border-radius: 10px; padding: 16px 20px; margin: 16px 0; }
+table { width: 100%; border-collapse: collapse; }
+th, td { padding: 4px 8px; text-align: left; border-bottom: 1px solid #3a4254; }
+td.number, th.number { text-align: right; }
+.star-4 { color: #ab65cf; }
+.star-5 { color: #e1d873; }
+details summary { cursor: pointer; margin: 8px 0; }
+svg { max-width: 100%; height: auto; }
+"#;
+
+fn format_average(summary: &StatisticsSummary) -> String {
+    summary
+        .average_pity()
+        .map(|pity| format!("{:.1}", pity))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn item_name(item: &GachaStatisticsDataItem) -> String {
+    resource_name(item.resource_id, i18n::language(), &item.name)
+}
+
+fn display_uid(player_id: &str, options: ExportOptions) -> String {
+    if options.mask_uid { mask_uid(player_id) } else { player_id.to_string() }
+}
+
+// 各条记录对应的抽数，取自统计数据中按时间顺序记录的五星及四星抽数，与界面展示保持一致，3星不计算
+fn pity_by_record(records: &[GachaData], data: &GachaStatisticsData) -> Vec<Option<i32>> {
+    let mut five_star_pity = data.detail.iter().map(|item| item.count);
+    let mut four_star_pity = data.four_star_detail.iter().map(|item| item.count);
+    records
+        .iter()
+        .map(|record| match record.quality_level {
+            5 => five_star_pity.next(),
+            4 => four_star_pity.next(),
+            _ => None,
+        })
+        .collect()
+}
+
+// 以内联 SVG 绘制出金抽数分布的柱状图
+fn histogram_svg(detail: &[GachaStatisticsDataItem]) -> String {
+    let bins = pity_histogram(detail, HISTOGRAM_BIN_WIDTH);
+    let max_count = bins.iter().map(|(_, count)| *count).max().unwrap_or_default().max(1);
+    let (left, bottom, top) = (8.0, 20.0, 16.0);
+    let slot = (HISTOGRAM_WIDTH - left * 2.0) / bins.len().max(1) as f32;
+    let chart_height = HISTOGRAM_HEIGHT - bottom - top;
+
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+        HISTOGRAM_WIDTH, HISTOGRAM_HEIGHT
+    );
+    for (index, (start, count)) in bins.iter().enumerate() {
+        let x = left + slot * index as f32;
+        let height = chart_height * *count as f32 / max_count as f32;
+        let y = top + chart_height - height;
+        if *count > 0 {
+            let _ = write!(
+                svg,
+                r##"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" rx="2" fill="#e1d873"/>"##,
+                x + 2.0,
+                y,
+                slot - 4.0,
+                height
+            );
+            let _ = write!(
+                svg,
+                r##"<text x="{:.1}" y="{:.1}" font-size="11" text-anchor="middle" fill="#e8e8e8">{}</text>"##,
+                x + slot / 2.0,
+                y - 3.0,
+                count
+            );
+        }
+        let _ = write!(
+            svg,
+            r##"<text x="{:.1}" y="{:.1}" font-size="11" text-anchor="middle" fill="#a0a8b8">{}</text>"##,
+            x + slot / 2.0,
+            HISTOGRAM_HEIGHT - 6.0,
+            start
+        );
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// 生成可离线查看的 HTML 报告，样式及图表均内联在文件中
+pub(crate) fn render_html(
+    player_id: &str,
+    statistics: &GachaStatistics,
+    history: &SavedGachaData,
+    options: ExportOptions,
+) -> String {
+    let uid = display_uid(player_id, options);
+    let date = Local::now().format("%Y-%m-%d");
+    let mut html = String::new();
+
+    let _ = writeln!(html, "<!DOCTYPE html>");
+    let _ = writeln!(html, r#"<html lang="{}">"#, i18n::language().code());
+    let _ = writeln!(html, r#"<head><meta charset="utf-8"><meta name="viewport" content="width=device-width, initial-scale=1">"#);
+    let _ = writeln!(html, "<title>{} - {}</title>", escape_xml(&t!("export.title")), escape_xml(&uid));
+    let _ = writeln!(html, "<style>{}</style></head>", REPORT_STYLE);
+    let _ = writeln!(html, "<body><main>");
+    let _ = writeln!(html, "<h1>{}</h1>", escape_xml(&t!("export.title")));
+    let _ = writeln!(html, r#"<p class="weak">{}</p>"#, escape_xml(&t!("export.subtitle", uid = uid, date = date)));
+
+    // 总览
+    let _ = writeln!(html, "<section><h2>{}</h2>", escape_xml(&t!("export.overview")));
+    let _ = writeln!(
+        html,
+        r#"<table><tr><th>{}</th><th class="number">{}</th><th class="number">{}</th><th class="number">{}</th><th class="number">{}</th><th class="number">{}</th></tr>"#,
+        escape_xml(&t!("label.aggregate_pool")),
+        escape_xml(&t!("label.aggregate_total")),
+        escape_xml(&t!("label.five_star")),
+        escape_xml(&t!("label.four_star")),
+        escape_xml(&t!("label.aggregate_current_pity")),
+        escape_xml(&t!("label.aggregate_average_pity")),
+    );
+    for (card_pool_type, data) in statistics.iter() {
+        let _ = writeln!(
+            html,
+            r#"<tr><td>{}</td><td class="number">{}</td><td class="number">{}</td><td class="number">{}</td><td class="number">{}</td><td class="number">{}</td></tr>"#,
+            escape_xml(&card_pool_name(*card_pool_type)),
+            data.total,
+            data.five_count,
+            data.four_count,
+            data.pull_count,
+            format_average(&StatisticsSummary::from_data(data)),
+        );
+    }
+    let _ = writeln!(html, "</table>");
+    if statistics.is_empty() {
+        let _ = writeln!(html, r#"<p class="weak">{}</p>"#, escape_xml(&t!("label.no_activity")));
+    }
+    let _ = writeln!(html, "</section>");
+
+    // 摘要卡片中已包含各卡池的星级占比饼图
+    let _ = writeln!(html, "<section>{}</section>", render_svg(&summary_card(player_id, statistics, options)));
+
+    for (card_pool_type, data) in statistics.iter() {
+        let _ = writeln!(html, "<section><h2>{}</h2>", escape_xml(&card_pool_name(*card_pool_type)));
+        let _ = writeln!(
+            html,
+            "<p>{}</p>",
+            escape_xml(&t!("label.pool_summary", total = data.total, pull = data.pull_count, five = data.five_count))
+        );
+        if !data.detail.is_empty() {
+            let five_stars: Vec<String> = data
+                .detail
+                .iter()
+                .map(|item| format!("{}[{}]", escape_xml(&item_name(item)), item.count))
+                .collect();
+            let _ = writeln!(html, r#"<p class="star-5">{}</p>"#, five_stars.join("　"));
+            let _ = writeln!(html, "<h3>{}</h3>{}", escape_xml(&t!("label.pity_charts")), histogram_svg(&data.detail));
+        }
+
+        let records = history.get(card_pool_type).map(Vec::as_slice).unwrap_or_default();
+        if !records.is_empty() {
+            let _ = writeln!(
+                html,
+                "<details><summary>{}</summary><table>",
+                escape_xml(&t!("export.history", count = records.len()))
+            );
+            let _ = writeln!(
+                html,
+                r#"<tr><th>{}</th><th>{}</th><th class="number">{}</th><th class="number">{}</th></tr>"#,
+                escape_xml(&t!("export.time")),
+                escape_xml(&t!("export.name")),
+                escape_xml(&t!("export.quality")),
+                escape_xml(&t!("export.pity")),
+            );
+            // 最新的记录排在最前
+            for (record, pity) in records.iter().zip(pity_by_record(records, data)).rev() {
+                let _ = writeln!(
+                    html,
+                    r#"<tr class="star-{}"><td>{}</td><td>{}</td><td class="number">{}</td><td class="number">{}</td></tr>"#,
+                    record.quality_level,
+                    options.time_zone.format(&record.time),
+                    escape_xml(&resource_name(record.resource_id, i18n::language(), &record.name)),
+                    record.quality_level,
+                    pity.map(|pity| pity.to_string()).unwrap_or_default(),
+                );
+            }
+            let _ = writeln!(html, "</table></details>");
+        }
+        let _ = writeln!(html, "</section>");
+    }
+
+    let _ = writeln!(html, r#"<p class="weak">{}</p>"#, escape_xml(&t!("export.generated_by", version = VERSION)));
+    let _ = writeln!(html, "</main></body></html>");
+    html
+}
+
+// Markdown 表格中的竖线需要转义
+// 名称中的 Markdown 标记符号按原样展示，资源名称可被用户的对照表覆盖
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        if matches!(char, '\\' | '`' | '*' | '_' | '[' | ']' | '(' | ')' | '<' | '>' | '#' | '|' | '~') {
+            escaped.push('\\');
+        }
+        escaped.push(char);
+    }
+    escaped
+}
+
+/// 生成便于粘贴到论坛或聊天中的 Markdown 摘要
+pub(crate) fn render_markdown(player_id: &str, statistics: &GachaStatistics, options: ExportOptions) -> String {
+    let uid = display_uid(player_id, options);
+    let mut markdown = String::new();
+
+    let _ = writeln!(markdown, "## {}\n", t!("export.title"));
+    let _ = writeln!(markdown, "{}\n", t!("export.subtitle", uid = uid, date = Local::now().format("%Y-%m-%d")));
+    if statistics.is_empty() {
+        let _ = writeln!(markdown, "{}", t!("label.no_activity"));
+        return markdown;
+    }
+
+    let _ = writeln!(
+        markdown,
+        "| {} | {} | {} | {} | {} | {} |",
+        t!("label.aggregate_pool"),
+        t!("label.aggregate_total"),
+        t!("label.five_star"),
+        t!("label.four_star"),
+        t!("label.aggregate_current_pity"),
+        t!("label.aggregate_average_pity"),
+    );
+    let _ = writeln!(markdown, "| --- | ---: | ---: | ---: | ---: | ---: |");
+    for (card_pool_type, data) in statistics.iter() {
+        let _ = writeln!(
+            markdown,
+            "| {} | {} | {} | {} | {} | {} |",
+            escape_markdown(&card_pool_name(*card_pool_type)),
+            data.total,
+            data.five_count,
+            data.four_count,
+            data.pull_count,
+            format_average(&StatisticsSummary::from_data(data)),
+        );
+    }
+
+    for (card_pool_type, data) in statistics.iter().filter(|(_, data)| !data.detail.is_empty()) {
+        let five_stars: Vec<String> = data
+            .detail
+            .iter()
+            .map(|item| format!("{}[{}]", escape_markdown(&item_name(item)), item.count))
+            .collect();
+        let _ = writeln!(markdown, "\n**{}**：{}", escape_markdown(&card_pool_name(*card_pool_type)), five_stars.join(" "));
+    }
+    markdown
+}
+
+#[test]
+fn render_report_test() {
+    use chrono::{DateTime, Utc};
+
+    let time: DateTime<Utc> = "2024-05-23T10:00:00Z".parse().unwrap();
+    let item = GachaStatisticsDataItem { name: "维里奈".to_string(), count: 2, resource_id: 1503, time, ..Default::default() };
+    let statistics = GachaStatistics::from([(
+        1,
+        GachaStatisticsData {
+            card_pool_type: 1,
+            total: 3,
+            five_count: 1,
+            four_count: 1,
+            three_count: 1,
+            pull_count: 1,
+            detail: vec![item],
+            four_star_detail: vec![GachaStatisticsDataItem { count: 1, time, ..Default::default() }],
+            ..Default::default()
+        },
+    )]);
+    let records: Vec<GachaData> = [4, 5, 3]
+        .into_iter()
+        .map(|quality_level| {
+            let mut record = GachaData::default();
+            record.quality_level = quality_level;
+            record.name = format!("<{}星>", quality_level);
+            record.time = time;
+            record
+        })
+        .collect();
+    assert_eq!(pity_by_record(&records, &statistics[&1]), vec![Some(1), Some(2), None]);
+    let history = SavedGachaData::from([(1, records)]);
+
+    let options = ExportOptions { mask_uid: true, ..Default::default() };
+    let html = render_html("100123456", &statistics, &history, options);
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(!html.contains("100123456"));
+    // 不引用任何外部资源
+    assert!(!html.contains("<link") && !html.contains("src="));
+    assert!(html.contains("&lt;5星&gt;"));
+    assert_eq!(html.matches(r#"<tr class="star-"#).count(), 3);
+
+    let markdown = render_markdown("100123456", &statistics, options);
+    assert!(markdown.contains("10*****56"));
+    assert!(markdown.contains("| --- |"));
+    assert!(markdown.contains("[2]"));
+    assert_eq!(escape_markdown("*a_b* [c] | <d>"), "\\*a\\_b\\* \\[c\\] \\| \\<d\\>");
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use serde::Deserialize;
+use tracing::{info, warn};
+use crate::i18n::Language;
+
+// 本地图标包目录，未指定图标路径的资源使用以资源 ID 命名的图标，如 1102.png
+pub(crate) const ICON_DIR: &str = "./data/icons";
+// 用户更新的资源对照表，其中的资源会覆盖随程序打包的同 ID 资源
+const CATALOGUE_OVERRIDE_PATH: &str = "./data/catalogue.toml";
+
+#[derive(Deserialize, Debug, Default)]
+struct ResourceCatalogue {
+    resource: Vec<ResourceInfo>,
+}
+
+// 资源信息，name 以语言代码为键，其余字段均可省略
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct ResourceInfo {
+    pub(crate) id: i32,
+    pub(crate) name: HashMap<String, String>,
+    #[serde(default)]
+    pub(crate) rarity: Option<i32>,
+    // 武器没有属性
+    #[serde(default)]
+    pub(crate) element: Option<Element>,
+    #[serde(default)]
+    pub(crate) weapon_type: Option<WeaponType>,
+    // 首次登场的版本号，如 1.0
+    #[serde(default)]
+    pub(crate) release: Option<String>,
+    // 相对于图标包目录的图标路径
+    #[serde(default)]
+    pub(crate) icon: Option<String>,
+}
+
+/// 角色属性
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Element {
+    Glacio,
+    Fusion,
+    Electro,
+    Aero,
+    Spectro,
+    Havoc,
+}
+
+impl Element {
+    pub(crate) fn key(&self) -> &'static str {
+        match self {
+            Element::Glacio => "glacio",
+            Element::Fusion => "fusion",
+            Element::Electro => "electro",
+            Element::Aero => "aero",
+            Element::Spectro => "spectro",
+            Element::Havoc => "havoc",
+        }
+    }
+}
+
+/// 武器类型，角色为可使用的武器类型
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum WeaponType {
+    Broadblade,
+    Sword,
+    Pistols,
+    Gauntlets,
+    Rectifier,
+}
+
+impl WeaponType {
+    pub(crate) fn key(&self) -> &'static str {
+        match self {
+            WeaponType::Broadblade => "broadblade",
+            WeaponType::Sword => "sword",
+            WeaponType::Pistols => "pistols",
+            WeaponType::Gauntlets => "gauntlets",
+            WeaponType::Rectifier => "rectifier",
+        }
+    }
+}
+
+fn parse_catalogue(source: &str) -> Result<Vec<ResourceInfo>, toml::de::Error> {
+    toml::from_str::<ResourceCatalogue>(source).map(|catalogue| catalogue.resource)
+}
+
+// 随程序打包的资源对照表，再合并用户更新的对照表
+static CATALOGUE: LazyLock<HashMap<i32, ResourceInfo>> = LazyLock::new(|| {
+    let mut catalogue: HashMap<i32, ResourceInfo> = parse_catalogue(include_str!("../resource/catalogue/resources.toml"))
+        .unwrap_or_else(|err| {
+            warn!("资源对照表解析失败：{}", err);
+            vec![]
+        })
+        .into_iter()
+        .map(|info| (info.id, info))
+        .collect();
+
+    // 对照表格式有误时忽略整个文件，继续使用随程序打包的对照表
+    if let Ok(source) = fs::read_to_string(CATALOGUE_OVERRIDE_PATH) {
+        match parse_catalogue(&source) {
+            Ok(resources) => {
+                info!("加载资源对照表 {}，共 {} 项", CATALOGUE_OVERRIDE_PATH, resources.len());
+                catalogue.extend(resources.into_iter().map(|info| (info.id, info)));
+            }
+            Err(err) => warn!("资源对照表 {} 解析失败：{}", CATALOGUE_OVERRIDE_PATH, err),
+        }
+    }
+    catalogue
+});
+
+pub(crate) fn resource_info(resource_id: i32) -> Option<&'static ResourceInfo> {
+    CATALOGUE.get(&resource_id)
+}
+
+/// 按展示语言获取资源名称，未收录时使用抽卡记录中保存的名称
+pub(crate) fn resource_name(resource_id: i32, language: Language, fallback: &str) -> String {
+    resource_info(resource_id)
+        .and_then(|info| info.name.get(language.code()))
+        .cloned()
+        .unwrap_or_else(|| fallback.to_string())
+}
+
+/// 资源图标的路径，是否存在由调用方判断
+pub(crate) fn resource_icon_path(resource_id: i32) -> PathBuf {
+    let icon = resource_info(resource_id)
+        .and_then(|info| info.icon.clone())
+        .unwrap_or_else(|| format!("{}.png", resource_id));
+    PathBuf::from(ICON_DIR).join(icon)
+}
+
+#[test]
+fn resource_name_test() {
+    assert_eq!(resource_name(1102, Language::En, "散华"), "Sanhua");
+    assert_eq!(resource_name(1102, Language::ZhHans, "Sanhua"), "散华");
+    // 未收录的资源及语言均回退到原名称
+    assert_eq!(resource_name(-1, Language::En, "未知"), "未知");
+    assert_eq!(resource_name(21010043, Language::Ja, "远行者长刃·辟路"), "遠行者長刃・辟路");
+}
+
+#[test]
+fn resource_metadata_test() {
+    let info = resource_info(1505).unwrap();
+    assert_eq!((info.rarity, info.element, info.weapon_type), (Some(5), Some(Element::Spectro), Some(WeaponType::Rectifier)));
+    assert_eq!(info.release.as_deref(), Some("1.3"));
+    let info = resource_info(21050036).unwrap();
+    assert_eq!((info.rarity, info.element, info.weapon_type), (Some(5), None, Some(WeaponType::Rectifier)));
+    // 每个资源都有星级及武器类型
+    for info in CATALOGUE.values() {
+        assert!(info.rarity.is_some() && info.weapon_type.is_some(), "资源 {} 缺少信息", info.id);
+        for language in Language::ALL {
+            assert!(info.name.contains_key(language.code()), "资源 {} 缺少 {:?} 名称", info.id, language);
+        }
+    }
+    // 各星级的各类武器至少收录一个
+    let weapon_types = [WeaponType::Broadblade, WeaponType::Sword, WeaponType::Pistols, WeaponType::Gauntlets, WeaponType::Rectifier];
+    for rarity in 3..=5 {
+        for weapon_type in weapon_types {
+            assert!(
+                CATALOGUE.values().any(|info| info.element.is_none() && info.rarity == Some(rarity) && info.weapon_type == Some(weapon_type)),
+                "缺少 {} 星 {:?}",
+                rarity,
+                weapon_type
+            );
+        }
+    }
+    // 角色的各星级均有收录
+    for rarity in 4..=5 {
+        assert!(CATALOGUE.values().any(|info| info.element.is_some() && info.rarity == Some(rarity)), "缺少 {} 星角色", rarity);
+    }
+    assert_eq!(resource_icon_path(1102), PathBuf::from(ICON_DIR).join("1102.png"));
+    assert_eq!(resource_icon_path(-1), PathBuf::from(ICON_DIR).join("-1.png"));
+
+    let resources = parse_catalogue("[[resource]]\nid = 1\nname = { en = \"Test\" }\nicon = \"a/b.png\"").unwrap();
+    assert_eq!(resources[0].icon.as_deref(), Some("a/b.png"));
+    assert!(resources[0].element.is_none());
+    assert!(parse_catalogue("[[resource]]\nid = 1\nname = {}\nelement = \"Unknown\"").is_err());
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use crate::core::error::CoreError;
+use crate::core::gacha::{load_saved_gacha_data, SavedGachaData};
+use crate::core::resource::resource_info;
+
+// 共鸣链及精炼的最高等级
+const MAX_RESONANCE_CHAIN: i32 = 6;
+const MAX_REFINEMENT: i32 = 5;
+
+// 各语言接口返回的资源类型名称
+const RESONATOR_TYPES: [&str; 4] = ["角色", "Resonator", "Resonators", "共鳴者"];
+const WEAPON_TYPES: [&str; 3] = ["武器", "Weapon", "Weapons"];
+
+/// 资源的种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum ResourceKind {
+    Resonator,
+    Weapon,
+}
+
+impl ResourceKind {
+    // 优先按接口返回的类型判断，无法识别时按 ID 判断，角色 ID 为四位数，武器 ID 为八位数
+    pub(crate) fn from_record(resource_id: i32, resource_type: &str) -> Self {
+        if RESONATOR_TYPES.contains(&resource_type) {
+            ResourceKind::Resonator
+        } else if WEAPON_TYPES.contains(&resource_type) || resource_id >= 10_000_000 {
+            ResourceKind::Weapon
+        } else {
+            ResourceKind::Resonator
+        }
+    }
+}
+
+/// 已获取的角色或武器，按抽卡记录推算，不包含通过其他途径获取的数量
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RosterEntry {
+    pub(crate) resource_id: i32,
+    // 最近一条记录中的名称及类型
+    pub(crate) name: String,
+    pub(crate) resource_type: String,
+    pub(crate) kind: ResourceKind,
+    pub(crate) quality_level: i32,
+    pub(crate) copies: i32,
+    pub(crate) first_obtained: DateTime<Utc>,
+    pub(crate) last_obtained: DateTime<Utc>,
+}
+
+impl RosterEntry {
+    /// 角色的共鸣链（0 - 6）或武器的精炼等级（1 - 5）
+    pub(crate) fn rank(&self) -> i32 {
+        match self.kind {
+            ResourceKind::Resonator => (self.copies - 1).min(MAX_RESONANCE_CHAIN),
+            ResourceKind::Weapon => self.copies.min(MAX_REFINEMENT),
+        }
+    }
+
+    /// 达到最高等级后多出的数量
+    pub(crate) fn surplus(&self) -> i32 {
+        match self.kind {
+            ResourceKind::Resonator => (self.copies - 1 - MAX_RESONANCE_CHAIN).max(0),
+            ResourceKind::Weapon => (self.copies - MAX_REFINEMENT).max(0),
+        }
+    }
+}
+
+/// 用户的全部角色及武器
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct Roster {
+    pub(crate) player_id: String,
+    pub(crate) entries: Vec<RosterEntry>,
+}
+
+/// 汇总全部卡池的抽卡记录，按星级、数量及最近获取时间排序
+pub(crate) fn build_roster(data: &SavedGachaData) -> Vec<RosterEntry> {
+    let mut entries: HashMap<i32, RosterEntry> = HashMap::new();
+    for record in data.values().flatten() {
+        let entry = entries.entry(record.resource_id).or_insert_with(|| RosterEntry {
+            resource_id: record.resource_id,
+            name: record.name.clone(),
+            resource_type: record.resource_type.clone(),
+            kind: ResourceKind::from_record(record.resource_id, &record.resource_type),
+            // 记录中缺少星级时按资源对照表补全
+            quality_level: if record.quality_level > 0 {
+                record.quality_level
+            } else {
+                resource_info(record.resource_id).and_then(|info| info.rarity).unwrap_or_default()
+            },
+            copies: 0,
+            first_obtained: record.time,
+            last_obtained: record.time,
+        });
+        entry.copies += 1;
+        entry.first_obtained = entry.first_obtained.min(record.time);
+        if record.time >= entry.last_obtained {
+            entry.last_obtained = record.time;
+            entry.name = record.name.clone();
+            entry.resource_type = record.resource_type.clone();
+        }
+    }
+
+    let mut entries: Vec<RosterEntry> = entries.into_values().collect();
+    entries.sort_by(|a, b| {
+        b.quality_level
+            .cmp(&a.quality_level)
+            .then(b.copies.cmp(&a.copies))
+            .then(b.last_obtained.cmp(&a.last_obtained))
+            .then(a.resource_id.cmp(&b.resource_id))
+    });
+    entries
+}
+
+/// 读取用户保存的抽卡记录并生成角色及武器列表
+pub(crate) fn roster(player_id: String) -> Result<Roster, CoreError> {
+    let data = load_saved_gacha_data(&player_id)?;
+    Ok(Roster { player_id, entries: build_roster(&data) })
+}
+
+#[test]
+fn build_roster_test() {
+    use chrono::TimeZone;
+    use crate::core::gacha::GachaData;
+
+    let record = |resource_id, resource_type: &str, quality_level, day| {
+        let mut record = GachaData::default();
+        record.resource_id = resource_id;
+        record.resource_type = resource_type.to_string();
+        record.quality_level = quality_level;
+        record.name = resource_id.to_string();
+        record.time = Utc.with_ymd_and_hms(2024, 6, day, 0, 0, 0).unwrap();
+        record
+    };
+    let mut data = SavedGachaData::new();
+    data.insert(1, (1..=9).map(|day| record(1102, "角色", 4, day)).collect());
+    data.insert(2, vec![record(21010043, "武器", 3, 3), record(1503, "Resonator", 5, 2)]);
+    data.insert(5, vec![record(21010043, "武器", 3, 1)]);
+
+    let entries = build_roster(&data);
+    assert_eq!(entries.iter().map(|entry| entry.resource_id).collect::<Vec<_>>(), vec![1503, 1102, 21010043]);
+
+    let resonator = &entries[1];
+    assert_eq!((resonator.kind, resonator.copies, resonator.rank(), resonator.surplus()), (ResourceKind::Resonator, 9, 6, 2));
+    assert_eq!(resonator.first_obtained, Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap());
+    assert_eq!(resonator.last_obtained, Utc.with_ymd_and_hms(2024, 6, 9, 0, 0, 0).unwrap());
+
+    let weapon = &entries[2];
+    assert_eq!((weapon.kind, weapon.copies, weapon.rank(), weapon.surplus()), (ResourceKind::Weapon, 2, 2, 0));
+    assert_eq!(entries[0].rank(), 0);
+    // 无法识别的类型按 ID 判断
+    assert_eq!(ResourceKind::from_record(21050016, "Arme"), ResourceKind::Weapon);
+}
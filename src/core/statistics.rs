@@ -1,10 +1,14 @@
 use std::collections::BTreeMap;
-use std::fs::OpenOptions;
-use std::io::{Read, Write};
-use anyhow::Error;
+use std::fs;
+use std::sync::atomic::AtomicBool;
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use tracing::info;
-use crate::core::gacha::get_gacha_data;
+use crate::core::error::CoreError;
+use crate::i18n::t;
+use crate::core::gacha::{get_gacha_data, load_saved_gacha_data, FailedPools, GachaData, SavedGachaData};
+use crate::core::http::RequestConfig;
+use crate::core::region::DisplayTimeZone;
 use crate::view::main_view::UiRepaintSender;
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
@@ -17,6 +21,10 @@ pub(crate) struct GachaStatisticsData {
     pub(crate) three_count: i32,
     pub(crate) pull_count: i32,
     pub(crate) detail: Vec<GachaStatisticsDataItem>,
+    // 四星记录，抽数为距上次出四星或五星的抽数
+    pub(crate) four_star_detail: Vec<GachaStatisticsDataItem>,
+    // 距上次出四星或五星的抽数
+    pub(crate) four_pull_count: i32,
 }
 
 // 抽卡统计详情
@@ -27,16 +35,41 @@ pub(crate) struct GachaStatisticsDataItem {
     pub(crate) count: i32,
     pub(crate) resource_id: i32,
     pub(crate) resource_type: String,
+    // 抽取时间（UTC）
+    pub(crate) time: DateTime<Utc>,
 }
 
 pub(crate) type GachaStatistics = BTreeMap<i32, GachaStatisticsData>;
 
-pub(crate) async fn gacha_statistics(player_id: String, server_sender: &UiRepaintSender) -> Result<(String, GachaStatistics), Error> {
+// 统计缓存的格式版本，统计字段变化时递增，旧版本的缓存会从抽卡数据重新生成
+const STATISTICS_CACHE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct StatisticsCache {
+    version: u32,
+    statistics: GachaStatistics,
+}
+
+pub(crate) async fn gacha_statistics(
+    player_id: String,
+    request_config: &RequestConfig,
+    cancel: &AtomicBool,
+    server_sender: &UiRepaintSender,
+) -> Result<(String, GachaStatistics, FailedPools), CoreError> {
     // 从服务获取抽卡数据
-    let (player_id, gacha_data) = get_gacha_data(player_id, server_sender).await?;
+    let (player_id, gacha_data, failed_pools) = get_gacha_data(player_id, request_config, cancel, server_sender).await?;
 
-    let mut statistics: GachaStatistics = GachaStatistics::new();
+    let statistics = build_statistics(gacha_data);
+    info!("统计数据完毕");
+    // 数据处理完毕后写入缓存文件
+    save_statistics_cache(&player_id, &statistics)?;
+
+    Ok((player_id, statistics, failed_pools))
+}
 
+// 按卡池统计抽卡数据
+fn build_statistics(gacha_data: SavedGachaData) -> GachaStatistics {
+    let mut statistics: GachaStatistics = GachaStatistics::new();
     for (card_pool_type, data) in gacha_data {
         let mut statistics_data = GachaStatisticsData {
             card_pool_type,
@@ -46,68 +79,545 @@ pub(crate) async fn gacha_statistics(player_id: String, server_sender: &UiRepain
             three_count: 0,
             pull_count: 0,
             detail: vec![],
+            four_star_detail: vec![],
+            four_pull_count: 0,
         };
 
         // 累计抽数（出金清零）
         let mut inner_count = 0;
+        // 四星保底抽数（出四星或五星清零）
+        let mut four_star_count = 0;
         // 出金抽数（用于统计未出金抽数）
         let mut get_five_pull_count = 0;
         for item in data {
             inner_count += 1;
+            four_star_count += 1;
             statistics_data.total += 1;
 
             match item.quality_level {
                 5 => {
                     statistics_data.five_count += 1;
+                    four_star_count = 0;
 
                     statistics_data.detail.push(GachaStatisticsDataItem {
                         name: item.name,
                         count: inner_count,
                         resource_id: item.resource_id,
                         resource_type: item.resource_type,
+                        time: item.time,
                     });
 
                     get_five_pull_count += inner_count;
                     inner_count = 0;
                 }
-                4 => { statistics_data.four_count += 1; }
+                4 => {
+                    statistics_data.four_count += 1;
+
+                    statistics_data.four_star_detail.push(GachaStatisticsDataItem {
+                        name: item.name,
+                        count: four_star_count,
+                        resource_id: item.resource_id,
+                        resource_type: item.resource_type,
+                        time: item.time,
+                    });
+
+                    four_star_count = 0;
+                }
                 3 => { statistics_data.three_count += 1; }
                 _ => {}
             }
             statistics_data.pull_count = statistics_data.total - get_five_pull_count;
+            statistics_data.four_pull_count = four_star_count;
 
             statistics.insert(card_pool_type, statistics_data.clone());
         }
     }
 
-    info!("统计数据完毕");
-    // 数据处理完毕后写入缓存文件
-    let file_path = format!("data/{}/gacha_statistic_cache.json", player_id);
-
-    let mut file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(&file_path)?;
-    file.write_all(&*serde_json::to_vec(&statistics)?)?;
-
-    Ok((player_id, statistics))
+    statistics
 }
 
-// 从缓存文件中获取统计数据
-pub(crate) fn gacha_statistics_from_cache(player_id: String) -> Result<GachaStatistics, Error> {
+fn save_statistics_cache(player_id: &str, statistics: &GachaStatistics) -> Result<(), CoreError> {
     let file_path = format!("./data/{}/gacha_statistic_cache.json", player_id);
+    let data = serde_json::to_vec(&StatisticsCache { version: STATISTICS_CACHE_VERSION, statistics: statistics.clone() })
+        .map_err(|err| CoreError::Internal(t!("error.detail.serialize_statistics", detail = err)))?;
+    fs::write(&file_path, data).map_err(|err| CoreError::io(&file_path, err))
+}
 
-    let mut file = OpenOptions::new()
-        .read(true)
-        .open(&file_path)?;
-    let mut buffer = String::new();
-    file.read_to_string(&mut buffer)?;
+/// 从缓存文件中获取统计数据
+///
+/// 旧版本的缓存缺少新增的字段（如抽取时间），此时从已保存的抽卡数据重新统计并覆盖缓存
+pub(crate) fn gacha_statistics_from_cache(player_id: String) -> Result<GachaStatistics, CoreError> {
+    let file_path = format!("./data/{}/gacha_statistic_cache.json", player_id);
 
+    let buffer = fs::read_to_string(&file_path).map_err(|_| CoreError::NoCache)?;
     if buffer.is_empty() {
-        return Err(Error::msg("无缓存"));
+        return Err(CoreError::NoCache);
     }
 
-    let statistics = serde_json::from_str::<GachaStatistics>(&*buffer)?;
+    match serde_json::from_str::<StatisticsCache>(&buffer) {
+        Ok(cache) if cache.version == STATISTICS_CACHE_VERSION => return Ok(cache.statistics),
+        _ => info!("用户 {} 的统计缓存已过期，重新统计", player_id),
+    }
+
+    let gacha_data = load_saved_gacha_data(&player_id)?;
+    if gacha_data.is_empty() {
+        return Err(CoreError::NoCache);
+    }
+    let statistics = build_statistics(gacha_data);
+    save_statistics_cache(&player_id, &statistics)?;
     Ok(statistics)
-}
\ No newline at end of file
+}
+
+// 五星概率模型，按公开概率近似：基础概率 0.8%，第 66 抽起每抽提升 4%，第 80 抽必出
+const BASE_FIVE_RATE: f64 = 0.008;
+const SOFT_PITY_START: i32 = 66;
+const SOFT_PITY_STEP: f64 = 0.04;
+pub(crate) const HARD_PITY: i32 = 80;
+
+// 已垫 pull - 1 抽未出金时，第 pull 抽出金的概率
+fn five_star_rate(pull: i32) -> f64 {
+    if pull >= HARD_PITY {
+        1.0
+    } else if pull >= SOFT_PITY_START {
+        (BASE_FIVE_RATE + SOFT_PITY_STEP * (pull - SOFT_PITY_START + 1) as f64).min(1.0)
+    } else {
+        BASE_FIVE_RATE
+    }
+}
+
+/// 理论出金抽数分布，第 n 项为恰好在第 n + 1 抽出金的概率
+pub(crate) fn theoretical_pity_distribution() -> Vec<f64> {
+    let mut remaining = 1.0;
+    (1..=HARD_PITY)
+        .map(|pull| {
+            let probability = remaining * five_star_rate(pull);
+            remaining -= probability;
+            probability
+        })
+        .collect()
+}
+
+/// 出金抽数按 width 分组计数，返回各组的起始抽数及出金次数
+pub(crate) fn pity_histogram(detail: &[GachaStatisticsDataItem], width: i32) -> Vec<(i32, i32)> {
+    let width = width.max(1);
+    let max_pity = detail.iter().map(|item| item.count).max().unwrap_or_default().max(HARD_PITY);
+    let mut bins: Vec<_> = (1..=max_pity).step_by(width as usize).map(|start| (start, 0)).collect();
+    for item in detail.iter().filter(|item| item.count > 0) {
+        bins[((item.count - 1) / width) as usize].1 += 1;
+    }
+    bins
+}
+
+/// 垫抽数随抽数变化的折线，出金后归零；同时返回各五星所在的点位
+pub(crate) fn pity_timeline(data: &GachaStatisticsData) -> (Vec<[f64; 2]>, Vec<[f64; 2]>) {
+    let mut points = vec![[0.0, 0.0]];
+    let mut five_stars = vec![];
+    let mut pulls = 0;
+    // 抽卡记录按时间顺序排列，出金记录中的抽数即为两次出金的间隔
+    for item in data.detail.iter() {
+        pulls += item.count;
+        points.push([pulls as f64, item.count as f64]);
+        points.push([pulls as f64, 0.0]);
+        five_stars.push([pulls as f64, item.count as f64]);
+    }
+    if data.pull_count > 0 {
+        points.push([(pulls + data.pull_count) as f64, data.pull_count as f64]);
+    }
+    (points, five_stars)
+}
+
+/// 按时间汇总的周期
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Period {
+    Day,
+    // 以周一为一周的开始
+    Week,
+    Month,
+}
+
+impl Period {
+    /// 日期所在周期的第一天
+    pub(crate) fn start(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Day => date,
+            Period::Week => date - Days::new(date.weekday().num_days_from_monday() as u64),
+            Period::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+}
+
+/// 一个周期内的抽卡情况
+#[derive(Debug, PartialEq, Clone, Default)]
+pub(crate) struct PeriodActivity {
+    // 各卡池的抽数
+    pub(crate) pulls: BTreeMap<i32, i32>,
+    // 周期内获得的五星及所在卡池
+    pub(crate) five_stars: Vec<(i32, GachaData)>,
+}
+
+impl PeriodActivity {
+    pub(crate) fn total(&self) -> i32 {
+        self.pulls.values().sum()
+    }
+}
+
+/// 各周期的抽卡情况，以周期的第一天为键
+pub(crate) type Activity = BTreeMap<NaiveDate, PeriodActivity>;
+
+// 按周期汇总抽卡记录，日期按展示时区划分
+pub(crate) fn pull_activity(gacha_data: &SavedGachaData, period: Period, time_zone: DisplayTimeZone) -> Activity {
+    let mut activity = Activity::new();
+    for (card_pool_type, data) in gacha_data {
+        for item in data {
+            let start = period.start(time_zone.date(&item.time));
+            let period_activity = activity.entry(start).or_default();
+            *period_activity.pulls.entry(*card_pool_type).or_default() += 1;
+            if item.quality_level == 5 {
+                period_activity.five_stars.push((*card_pool_type, item.clone()));
+            }
+        }
+    }
+    activity
+}
+
+/// 用户按日、周、月汇总的抽卡情况
+#[derive(Debug, PartialEq, Clone, Default)]
+pub(crate) struct ActivityStatistics {
+    pub(crate) player_id: String,
+    pub(crate) daily: Activity,
+    pub(crate) weekly: Activity,
+    pub(crate) monthly: Activity,
+}
+
+impl ActivityStatistics {
+    pub(crate) fn period(&self, period: Period) -> &Activity {
+        match period {
+            Period::Day => &self.daily,
+            Period::Week => &self.weekly,
+            Period::Month => &self.monthly,
+        }
+    }
+}
+
+// 读取用户保存的抽卡记录并按日、周、月汇总
+pub(crate) fn activity_statistics(player_id: String, time_zone: DisplayTimeZone) -> Result<ActivityStatistics, CoreError> {
+    let gacha_data = load_saved_gacha_data(&player_id)?;
+    Ok(ActivityStatistics {
+        daily: pull_activity(&gacha_data, Period::Day, time_zone),
+        weekly: pull_activity(&gacha_data, Period::Week, time_zone),
+        monthly: pull_activity(&gacha_data, Period::Month, time_zone),
+        player_id,
+    })
+}
+
+/// 多个卡池或多个用户的汇总数据
+#[derive(Debug, PartialEq, Clone, Default)]
+pub(crate) struct StatisticsSummary {
+    pub(crate) total: i32,
+    pub(crate) five_count: i32,
+    // 未出金抽数，只对单个用户的单个卡池有意义，汇总后为空
+    pub(crate) pull_count: Option<i32>,
+    // 出金所用的总抽数，用于计算平均出金抽数
+    pub(crate) five_pulls: i32,
+}
+
+impl StatisticsSummary {
+    pub(crate) fn from_data(data: &GachaStatisticsData) -> Self {
+        StatisticsSummary {
+            total: data.total,
+            five_count: data.five_count,
+            pull_count: Some(data.pull_count),
+            five_pulls: data.detail.iter().map(|item| item.count).sum(),
+        }
+    }
+
+    fn merge(&mut self, other: &StatisticsSummary) {
+        self.total += other.total;
+        self.five_count += other.five_count;
+        // 不同卡池或不同用户的未出金抽数相加没有意义
+        self.pull_count = None;
+        self.five_pulls += other.five_pulls;
+    }
+
+    /// 平均出金抽数，没有出金时为空
+    pub(crate) fn average_pity(&self) -> Option<f64> {
+        (self.five_count > 0).then(|| self.five_pulls as f64 / self.five_count as f64)
+    }
+}
+
+/// 单个卡池当前一轮的抽卡情况及历次出金的抽数
+#[derive(Debug, PartialEq, Clone, Default)]
+pub(crate) struct PoolRunStatistics {
+    // 距上次出五星的抽数
+    pub(crate) five_pity: i32,
+    // 距上次出四星或五星的抽数
+    pub(crate) four_pity: i32,
+    pub(crate) average_five_pity: Option<f64>,
+    pub(crate) min_five_pity: Option<i32>,
+    pub(crate) max_five_pity: Option<i32>,
+    pub(crate) average_four_pity: Option<f64>,
+}
+
+impl PoolRunStatistics {
+    pub(crate) fn from_data(data: &GachaStatisticsData) -> Self {
+        let average = |items: &[GachaStatisticsDataItem]| {
+            (!items.is_empty()).then(|| items.iter().map(|item| item.count).sum::<i32>() as f64 / items.len() as f64)
+        };
+        PoolRunStatistics {
+            five_pity: data.pull_count,
+            four_pity: data.four_pull_count,
+            average_five_pity: average(&data.detail),
+            min_five_pity: data.detail.iter().map(|item| item.count).min(),
+            max_five_pity: data.detail.iter().map(|item| item.count).max(),
+            average_four_pity: average(&data.four_star_detail),
+        }
+    }
+}
+
+/// 单个卡池类型的汇总数据及各用户的贡献
+#[derive(Debug, PartialEq, Clone, Default)]
+pub(crate) struct PoolAggregate {
+    pub(crate) summary: StatisticsSummary,
+    pub(crate) accounts: Vec<(String, StatisticsSummary)>,
+}
+
+/// 多个用户的汇总统计
+#[derive(Debug, PartialEq, Clone, Default)]
+pub(crate) struct AggregateStatistics {
+    pub(crate) pools: BTreeMap<i32, PoolAggregate>,
+    // 全部卡池的汇总及各用户的贡献
+    pub(crate) overall: PoolAggregate,
+}
+
+// 按卡池类型汇总多个用户的统计数据
+pub(crate) fn aggregate_statistics(accounts: &[(String, GachaStatistics)]) -> AggregateStatistics {
+    let mut aggregate = AggregateStatistics::default();
+
+    for (player_id, statistics) in accounts {
+        let mut account_overall = StatisticsSummary::default();
+        for (card_pool_type, data) in statistics {
+            let summary = StatisticsSummary::from_data(data);
+
+            let pool = aggregate.pools.entry(*card_pool_type).or_default();
+            pool.summary.merge(&summary);
+            account_overall.merge(&summary);
+            pool.accounts.push((player_id.clone(), summary));
+        }
+        aggregate.overall.summary.merge(&account_overall);
+        aggregate.overall.accounts.push((player_id.clone(), account_overall));
+    }
+
+    aggregate
+}
+
+// 从缓存中读取多个用户的统计数据并汇总，没有缓存的用户不参与汇总
+pub(crate) fn aggregate_statistics_from_cache(player_ids: &[String]) -> Result<AggregateStatistics, CoreError> {
+    let mut accounts = vec![];
+    for player_id in player_ids {
+        match gacha_statistics_from_cache(player_id.clone()) {
+            Ok(statistics) => accounts.push((player_id.clone(), statistics)),
+            Err(CoreError::NoCache) => info!("用户 {} 没有缓存数据，不参与汇总", player_id),
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(aggregate_statistics(&accounts))
+}
+
+/// 两个用户同一卡池类型的对比
+#[derive(Debug, PartialEq, Clone, Default)]
+pub(crate) struct PoolComparison {
+    pub(crate) left: StatisticsSummary,
+    pub(crate) right: StatisticsSummary,
+    // 两个用户的出金记录分别按出金抽数排序后逐行对齐，较短的一侧补空
+    pub(crate) five_stars: Vec<(Option<GachaStatisticsDataItem>, Option<GachaStatisticsDataItem>)>,
+}
+
+/// 两个用户的对比统计
+#[derive(Debug, PartialEq, Clone, Default)]
+pub(crate) struct AccountComparison {
+    pub(crate) left_id: String,
+    pub(crate) right_id: String,
+    pub(crate) pools: BTreeMap<i32, PoolComparison>,
+}
+
+// 按卡池类型对比两个用户的统计数据，只有一方有数据的卡池另一方视为空
+pub(crate) fn compare_statistics(left: &GachaStatistics, right: &GachaStatistics) -> BTreeMap<i32, PoolComparison> {
+    let sorted_five_stars = |data: Option<&GachaStatisticsData>| {
+        let mut detail = data.map(|data| data.detail.clone()).unwrap_or_default();
+        detail.sort_by_key(|item| item.count);
+        detail
+    };
+
+    left.keys()
+        .chain(right.keys())
+        .map(|card_pool_type| {
+            let left_data = left.get(card_pool_type);
+            let right_data = right.get(card_pool_type);
+
+            let left_five_stars = sorted_five_stars(left_data);
+            let right_five_stars = sorted_five_stars(right_data);
+            let rows = left_five_stars.len().max(right_five_stars.len());
+            let five_stars = (0..rows)
+                .map(|index| (left_five_stars.get(index).cloned(), right_five_stars.get(index).cloned()))
+                .collect();
+
+            let comparison = PoolComparison {
+                left: left_data.map(StatisticsSummary::from_data).unwrap_or_default(),
+                right: right_data.map(StatisticsSummary::from_data).unwrap_or_default(),
+                five_stars,
+            };
+            (*card_pool_type, comparison)
+        })
+        .collect()
+}
+
+// 从缓存中读取两个用户的统计数据并对比，没有缓存的用户视为没有数据
+pub(crate) fn compare_statistics_from_cache(left_id: String, right_id: String) -> Result<AccountComparison, CoreError> {
+    let load = |player_id: &String| match gacha_statistics_from_cache(player_id.clone()) {
+        Err(CoreError::NoCache) => Ok(GachaStatistics::new()),
+        result => result,
+    };
+    let pools = compare_statistics(&load(&left_id)?, &load(&right_id)?);
+    Ok(AccountComparison { left_id, right_id, pools })
+}
+
+#[test]
+fn aggregate_statistics_test() {
+    let data = |card_pool_type: i32, total: i32, pull_count: i32, five_pulls: &[i32]| GachaStatisticsData {
+        card_pool_type,
+        total,
+        five_count: five_pulls.len() as i32,
+        pull_count,
+        detail: five_pulls
+            .iter()
+            .map(|count| GachaStatisticsDataItem { count: *count, ..Default::default() })
+            .collect(),
+        ..Default::default()
+    };
+
+    let first = GachaStatistics::from([(1, data(1, 150, 20, &[60, 70])), (2, data(2, 10, 10, &[]))]);
+    let second = GachaStatistics::from([(1, data(1, 80, 0, &[80]))]);
+    let aggregate = aggregate_statistics(&[("100".to_string(), first), ("200".to_string(), second)]);
+
+    let pool = &aggregate.pools[&1];
+    assert_eq!(pool.summary, StatisticsSummary { total: 230, five_count: 3, pull_count: None, five_pulls: 210 });
+    assert_eq!(pool.summary.average_pity(), Some(70.0));
+    // 各用户在单个卡池中的未出金抽数保持不变
+    let pull_counts: Vec<_> = pool.accounts.iter().map(|(_, summary)| summary.pull_count).collect();
+    assert_eq!(pull_counts, vec![Some(20), Some(0)]);
+    assert_eq!(aggregate.pools[&2].summary.average_pity(), None);
+
+    assert_eq!(aggregate.overall.summary.total, 240);
+    assert_eq!(aggregate.overall.summary.pull_count, None);
+    assert_eq!(aggregate.overall.accounts[0], ("100".to_string(), StatisticsSummary { total: 160, five_count: 2, pull_count: None, five_pulls: 130 }));
+}
+
+#[test]
+fn compare_statistics_test() {
+    let data = |five_pulls: &[i32]| GachaStatisticsData {
+        total: five_pulls.iter().sum(),
+        five_count: five_pulls.len() as i32,
+        detail: five_pulls
+            .iter()
+            .map(|count| GachaStatisticsDataItem { count: *count, ..Default::default() })
+            .collect(),
+        ..Default::default()
+    };
+
+    let left = GachaStatistics::from([(1, data(&[70, 20, 80]))]);
+    let right = GachaStatistics::from([(1, data(&[40])), (2, data(&[60]))]);
+    let pools = compare_statistics(&left, &right);
+
+    assert_eq!(pools.len(), 2);
+    let pity = |item: &Option<GachaStatisticsDataItem>| item.as_ref().map(|item| item.count);
+    let rows: Vec<_> = pools[&1].five_stars.iter().map(|(left, right)| (pity(left), pity(right))).collect();
+    assert_eq!(rows, vec![(Some(20), Some(40)), (Some(70), None), (Some(80), None)]);
+    assert_eq!(pools[&1].left.total, 170);
+    assert_eq!(pools[&2].left, StatisticsSummary::default());
+    assert_eq!(pools[&2].right.average_pity(), Some(60.0));
+}
+
+#[test]
+fn pity_distribution_test() {
+    let distribution = theoretical_pity_distribution();
+    assert_eq!(distribution.len(), HARD_PITY as usize);
+    assert!((distribution.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    assert!((distribution[0] - BASE_FIVE_RATE).abs() < 1e-12);
+
+    let item = |count: i32| GachaStatisticsDataItem { count, ..Default::default() };
+    let detail = vec![item(1), item(10), item(11), item(78), item(80)];
+    let bins = pity_histogram(&detail, 10);
+    assert_eq!(bins.len(), 8);
+    assert_eq!(bins[0], (1, 2));
+    assert_eq!(bins[1], (11, 1));
+    assert_eq!(bins[7], (71, 2));
+
+    let data = GachaStatisticsData { total: 35, pull_count: 5, detail: vec![item(20), item(10)], ..Default::default() };
+    let (points, five_stars) = pity_timeline(&data);
+    assert_eq!(five_stars, vec![[20.0, 20.0], [30.0, 10.0]]);
+    assert_eq!(points.last(), Some(&[35.0, 5.0]));
+    assert_eq!(points[2], [20.0, 0.0]);
+}
+
+#[test]
+fn pull_activity_test() {
+    let item = |quality_level: i32, time: &str| {
+        let mut data = GachaData::default();
+        data.quality_level = quality_level;
+        data.time = chrono::NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S").unwrap().and_utc();
+        data
+    };
+    let gacha_data = SavedGachaData::from([
+        (1, vec![item(3, "2024-05-30 10:00:00"), item(5, "2024-06-01 02:00:00"), item(4, "2024-06-03 12:00:00")]),
+        (2, vec![item(3, "2024-06-02 23:30:00")]),
+    ]);
+    let date = |text: &str| NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap();
+
+    let monthly = pull_activity(&gacha_data, Period::Month, DisplayTimeZone::Utc);
+    assert_eq!(monthly.len(), 2);
+    assert_eq!(monthly[&date("2024-06-01")].pulls, BTreeMap::from([(1, 2), (2, 1)]));
+    assert_eq!(monthly[&date("2024-06-01")].five_stars.len(), 1);
+    assert_eq!(monthly[&date("2024-05-01")].total(), 1);
+
+    // 2024-06-03 是周一
+    let weekly = pull_activity(&gacha_data, Period::Week, DisplayTimeZone::Utc);
+    assert_eq!(weekly.keys().copied().collect::<Vec<_>>(), vec![date("2024-05-27"), date("2024-06-03")]);
+    assert_eq!(weekly[&date("2024-05-27")].total(), 3);
+
+    // 按 UTC+8 计算时跨入下一天
+    let daily = pull_activity(&gacha_data, Period::Day, DisplayTimeZone::Server(crate::core::region::Region::China));
+    assert!(daily.contains_key(&date("2024-06-03")));
+    assert_eq!(daily[&date("2024-06-03")].total(), 2);
+}
+
+#[test]
+fn pool_run_statistics_test() {
+    let item = |count| GachaStatisticsDataItem { count, ..Default::default() };
+    let data = GachaStatisticsData {
+        pull_count: 12,
+        four_pull_count: 3,
+        detail: vec![item(70), item(40), item(79)],
+        four_star_detail: vec![item(10), item(6)],
+        ..Default::default()
+    };
+    let run = PoolRunStatistics::from_data(&data);
+    assert_eq!((run.five_pity, run.four_pity), (12, 3));
+    assert_eq!(run.average_five_pity, Some(63.0));
+    assert_eq!((run.min_five_pity, run.max_five_pity), (Some(40), Some(79)));
+    assert_eq!(run.average_four_pity, Some(8.0));
+    assert_eq!(PoolRunStatistics::from_data(&GachaStatisticsData::default()), PoolRunStatistics::default());
+}
+
+#[test]
+fn statistics_cache_test() {
+    let statistics = GachaStatistics::from([(1, GachaStatisticsData { card_pool_type: 1, total: 1, ..Default::default() })]);
+
+    // 旧版本直接保存统计数据，不能作为当前版本的缓存读取
+    let legacy = serde_json::to_string(&statistics).unwrap();
+    assert!(serde_json::from_str::<StatisticsCache>(&legacy).is_err());
+
+    let current = serde_json::to_string(&StatisticsCache { version: STATISTICS_CACHE_VERSION, statistics: statistics.clone() }).unwrap();
+    let cache = serde_json::from_str::<StatisticsCache>(&current).unwrap();
+    assert_eq!(cache.version, STATISTICS_CACHE_VERSION);
+    assert_eq!(cache.statistics, statistics);
+}
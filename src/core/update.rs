@@ -1,11 +1,25 @@
-use std::time::Duration;
-use anyhow::Error;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use ed25519_dalek::{Signature, VerifyingKey};
 use futures_util::stream::StreamExt;
 use ratelimit::Ratelimiter;
+use semver::Version;
+use reqwest::StatusCode;
+use reqwest::header::{CONTENT_RANGE, RANGE};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::fs::OpenOptions;
 use tokio::io::AsyncWriteExt;
+use tracing::info;
 
-use crate::core::message::MessageType::Normal;
+use crate::core::error::CoreError;
+use crate::i18n::t;
+use crate::core::http;
+use crate::core::install::PackageFormat;
+use crate::core::message::Event::DownloadProgressed;
 use crate::VERSION;
 use crate::view::main_view::UiRepaintSender;
 
@@ -15,6 +29,27 @@ pub(crate) struct Release {
     pub(crate) tag_name: String,
     pub(crate) body: String,
     pub(crate) assets: Vec<Assets>,
+    #[serde(default)]
+    pub(crate) prerelease: bool,
+    #[serde(default)]
+    pub(crate) draft: bool,
+    // 发布页面地址，自建来源可能没有
+    #[serde(default)]
+    pub(crate) html_url: String,
+}
+
+impl Release {
+    // 手动下载时打开的页面
+    pub(crate) fn page_url(&self) -> &str {
+        if self.html_url.is_empty() { RELEASES_PAGE_URL } else { &self.html_url }
+    }
+
+    /// 是否可以自动下载并校验：需要发布了签名清单，且当前程序内置了校验公钥
+    pub(crate) fn verifiable(&self) -> bool {
+        !UPDATE_PUBLIC_KEY.trim().is_empty()
+            && find_asset(&self.assets, MANIFEST_NAME).is_some()
+            && find_asset(&self.assets, SIGNATURE_NAME).is_some()
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -23,62 +58,584 @@ pub(crate) struct Assets {
     pub(crate) browser_download_url: String,
 }
 
-pub(crate) async fn check_update() -> Result<Release, Error> {
-    let client = reqwest::Client::new();
-    let result = client.get("https://api.github.com/repos/ningnao/wuthering-waves-gacha-record/releases/latest")
-        .header("User-Agent", "wuthering-waves-gacha-record")
+// 更新通道
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum UpdateChannel {
+    // 仅正式版
+    #[default]
+    Stable,
+    // 包含预发布版本
+    Beta,
+}
+
+// 发布信息及更新包的获取来源
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub(crate) enum UpdateSource {
+    #[default]
+    GitHub,
+    // GitHub 镜像，模板中的 {url} 替换为原始地址，如 https://mirror.example.com/{url}
+    Mirror(String),
+    // 自建的发布信息地址，返回与 GitHub 发布接口格式相同的 JSON，可以是单个发布或发布列表
+    Custom(String),
+}
+
+impl UpdateSource {
+    // 镜像模式下将 GitHub 地址替换为镜像地址
+    pub(crate) fn resolve(&self, url: &str) -> String {
+        match self {
+            UpdateSource::Mirror(template) if template.contains("{url}") => template.replace("{url}", url),
+            UpdateSource::Mirror(template) => format!("{}/{}", template.trim_end_matches('/'), url),
+            _ => url.to_string(),
+        }
+    }
+}
+
+// 自建发布信息可以是单个发布或发布列表
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ReleaseList {
+    One(Box<Release>),
+    Many(Vec<Release>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub(crate) struct UpdateConfig {
+    pub(crate) channel: UpdateChannel,
+    pub(crate) source: UpdateSource,
+    // 用户选择跳过的版本号
+    pub(crate) skipped_version: String,
+}
+
+const USER_AGENT: &str = "wuthering-waves-gacha-record";
+// 发布时随升级包一同上传的 SHA-256 清单及其分离签名
+const MANIFEST_NAME: &str = "SHA256SUMS";
+const SIGNATURE_NAME: &str = "SHA256SUMS.sig";
+// 校验清单签名的 Ed25519 公钥，由 scripts/sign-release.sh 生成，发布时使用对应的私钥签名
+// 为空时不支持自动更新，只提示用户手动下载
+const UPDATE_PUBLIC_KEY: &str = include_str!("../resource/update/public_key.hex");
+
+const RELEASES_URL: &str = "https://api.github.com/repos/ningnao/wuthering-waves-gacha-record/releases";
+const RELEASES_PAGE_URL: &str = "https://github.com/ningnao/wuthering-waves-gacha-record/releases";
+
+// 解析版本号，兼容 v 前缀
+pub(crate) fn parse_version(tag_name: &str) -> Option<Version> {
+    let tag_name = tag_name.trim();
+    Version::parse(tag_name.strip_prefix(['v', 'V']).unwrap_or(tag_name)).ok()
+}
+
+/// 从发布列表中选出比当前版本新、且未被跳过的最高版本
+pub(crate) fn select_update(releases: Vec<Release>, current: &Version, config: &UpdateConfig) -> Option<Release> {
+    let skipped_version = parse_version(&config.skipped_version);
+
+    releases
+        .into_iter()
+        .filter(|release| !release.draft)
+        // 无法解析的 tag（如 release）不视为更新
+        .filter_map(|release| parse_version(&release.tag_name).map(|version| (version, release)))
+        .filter(|(version, release)| {
+            config.channel == UpdateChannel::Beta || (!release.prerelease && version.pre.is_empty())
+        })
+        .filter(|(version, _)| version > current)
+        .filter(|(version, _)| skipped_version.as_ref() != Some(version))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, release)| release)
+}
+
+// 检查更新，无更新时返回 None
+pub(crate) async fn check_update(config: &UpdateConfig) -> Result<Option<Release>, CoreError> {
+    check_update_from(RELEASES_URL, config).await
+}
+
+async fn check_update_from(releases_url: &str, config: &UpdateConfig) -> Result<Option<Release>, CoreError> {
+    let current = parse_version(VERSION)
+        .ok_or_else(|| CoreError::Internal(t!("error.detail.invalid_version", version = VERSION)))?;
+
+    // 测试版通道需要读取完整的发布列表，/releases/latest 只返回最新的正式版
+    let url = match (&config.source, config.channel) {
+        (UpdateSource::Custom(url), _) => url.clone(),
+        (source, UpdateChannel::Stable) => source.resolve(&format!("{}/latest", releases_url)),
+        (source, UpdateChannel::Beta) => source.resolve(releases_url),
+    };
+    let response = http::client().get(url)
+        .header("User-Agent", USER_AGENT)
         .send()
         .await?
-        .json::<Release>()
-        .await;
+        .error_for_status()?;
+
+    let releases = match response.json::<ReleaseList>().await? {
+        ReleaseList::One(release) => vec![*release],
+        ReleaseList::Many(releases) => releases,
+    };
+
+    // 更新包同样从镜像下载
+    Ok(select_update(releases, &current, config).map(|mut release| {
+        for assets in release.assets.iter_mut() {
+            assets.browser_download_url = config.source.resolve(&assets.browser_download_url);
+        }
+        release
+    }))
+}
+
+/// 下载进度
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub(crate) struct DownloadProgress {
+    // 已下载的字节数，包含断点续传前已下载的部分
+    pub(crate) downloaded: u64,
+    // 文件总大小，未知时为 0
+    pub(crate) total: u64,
+    // 本次下载的平均速度（字节/秒）
+    pub(crate) bytes_per_second: f64,
+}
+
+impl DownloadProgress {
+    pub(crate) fn fraction(&self) -> f32 {
+        if self.total > 0 {
+            (self.downloaded as f64 / self.total as f64).min(1.0) as f32
+        } else {
+            0.0
+        }
+    }
+}
 
-    match result {
-        Ok(release) => {
-            if release.tag_name == format!("v{}", VERSION) {
-                return Err(Error::msg("无更新"));
+/// 下载当前平台的更新包，校验签名清单及 SHA-256 后返回保存路径
+///
+/// 下载内容先写入 `.part` 文件，中断或取消后再次下载时从断点继续，校验通过后才重命名为正式文件名
+pub(crate) async fn download_file(
+    release: Release,
+    filepath: String,
+    cancel: Arc<AtomicBool>,
+    service_tx: UiRepaintSender,
+) -> Result<PathBuf, CoreError> {
+    download_verified(&release, Path::new(&filepath), UPDATE_PUBLIC_KEY, &cancel, &service_tx).await
+}
+
+async fn download_verified(
+    release: &Release,
+    dir: &Path,
+    public_key: &str,
+    cancel: &AtomicBool,
+    service_tx: &UiRepaintSender,
+) -> Result<PathBuf, CoreError> {
+    let assets = select_asset(&release.assets, std::env::consts::OS, std::env::consts::ARCH)
+        .ok_or(CoreError::UpdateAssetMissing)?;
+
+    // 先校验清单签名，再从清单中取出升级包的哈希
+    let manifest_url = find_asset(&release.assets, MANIFEST_NAME).ok_or(CoreError::UpdateManifestMissing)?;
+    let signature_url = find_asset(&release.assets, SIGNATURE_NAME).ok_or(CoreError::UpdateManifestMissing)?;
+    let manifest = fetch_bytes(manifest_url).await?;
+    let signature = fetch_bytes(signature_url).await?;
+    verify_signature(&manifest, &signature, public_key)?;
+
+    let expected = parse_manifest(&String::from_utf8_lossy(&manifest))
+        .remove(&assets.name)
+        .ok_or(CoreError::UpdateManifestMissing)?;
+
+    let file_path = dir.join(&assets.name);
+    let part_path = dir.join(format!("{}.part", assets.name));
+    let display_path = part_path.display().to_string();
+
+    // 已下载部分需要计入哈希
+    let mut hasher = Sha256::new();
+    let mut downloaded = match tokio::fs::read(&part_path).await {
+        Ok(buffer) => {
+            hasher.update(&buffer);
+            buffer.len() as u64
+        }
+        Err(_) => 0,
+    };
+
+    // 下载升级包，存在未完成的文件时请求剩余部分
+    let mut request = http::client().get(&assets.browser_download_url)
+        .header("User-Agent", USER_AGENT);
+    if downloaded > 0 {
+        info!("从 {} 字节处继续下载 {}", downloaded, assets.name);
+        request = request.header(RANGE, format!("bytes={}-", downloaded));
+    }
+    let response = request.send().await?;
+
+    // 先确认响应可用再改动 .part 文件，出错时保留已下载的部分
+    let mut file = match response.status() {
+        StatusCode::PARTIAL_CONTENT => {
+            let start = response.headers().get(CONTENT_RANGE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(content_range_start);
+            if start != Some(downloaded) {
+                return Err(CoreError::DownloadResumeFailed {
+                    detail: t!("error.detail.content_range_mismatch", downloaded = downloaded),
+                });
             }
-            Ok(release)
+            OpenOptions::new().append(true).open(&part_path).await
         }
-        Err(err) => {
-            Err(Error::from(err))
+        // 已下载部分即为完整文件
+        StatusCode::RANGE_NOT_SATISFIABLE if downloaded > 0 => OpenOptions::new().append(true).open(&part_path).await,
+        StatusCode::OK => {
+            // 服务器不支持断点续传时从头下载
+            hasher = Sha256::new();
+            downloaded = 0;
+            tokio::fs::File::create(&part_path).await
         }
+        status => {
+            response.error_for_status()?;
+            return Err(CoreError::DownloadResumeFailed { detail: t!("error.detail.unexpected_status", status = status) });
+        }
+    }.map_err(|err| CoreError::io(&display_path, err))?;
+
+    let mut progress = DownloadProgress {
+        downloaded,
+        total: downloaded,
+        bytes_per_second: 0.0,
+    };
+    if response.status() != StatusCode::RANGE_NOT_SATISFIABLE {
+        progress.total += response.content_length().unwrap_or(0);
+
+        let started = Instant::now();
+        let mut stream = response.bytes_stream();
+
+        let limit = Ratelimiter::builder(1).period(Duration::from_millis(200)).build()
+            .map_err(|err| CoreError::Internal(err.to_string()))?;
+        while let Some(item) = stream.next().await {
+            // 取消时保留已下载的部分，以便下次继续
+            if cancel.load(Ordering::Relaxed) {
+                file.flush().await.map_err(|err| CoreError::io(&display_path, err))?;
+                return Err(CoreError::DownloadCancelled);
+            }
+
+            let chunk = item?;
+            hasher.update(&chunk);
+            file.write_all(&chunk).await.map_err(|err| CoreError::io(&display_path, err))?;
+            progress.downloaded += chunk.len() as u64;
+            progress.bytes_per_second = (progress.downloaded - downloaded) as f64 / started.elapsed().as_secs_f64().max(0.001);
+
+            if limit.try_wait().is_ok() {
+                let _ = service_tx.send(DownloadProgressed(progress));
+            }
+        }
+    }
+    file.flush().await.map_err(|err| CoreError::io(&display_path, err))?;
+    drop(file);
+    let _ = service_tx.send(DownloadProgressed(progress));
+
+    let actual = hex::encode(hasher.finalize());
+    if !actual.eq_ignore_ascii_case(&expected) {
+        // 校验失败的文件不保留，避免被误用或在其基础上续传
+        let _ = tokio::fs::remove_file(&part_path).await;
+        return Err(CoreError::UpdateChecksumMismatch { asset: assets.name.clone(), expected, actual });
+    }
+
+    tokio::fs::rename(&part_path, &file_path).await
+        .map_err(|err| CoreError::io(file_path.display().to_string(), err))?;
+
+    Ok(file_path)
+}
+
+/// 按操作系统及架构选择升级包，文件名形如 `wuthering-waves-gacha-record-v0.8.0-windows-x86_64.zip`
+///
+/// 早期发布的升级包名称中不含平台信息，只提供 Windows x64 版本
+pub(crate) fn select_asset<'a>(assets: &'a [Assets], os: &str, arch: &str) -> Option<&'a Assets> {
+    let os_aliases: &[&str] = match os {
+        "windows" => &["windows", "win64", "win"],
+        "macos" => &["macos", "darwin", "osx"],
+        other => &[other],
+    };
+    let arch_aliases: &[&str] = match arch {
+        "x86_64" => &["x86_64", "x64", "amd64"],
+        "aarch64" => &["aarch64", "arm64"],
+        "x86" => &["x86", "i686", "win32"],
+        other => &[other],
+    };
+
+    let mut packages = assets
+        .iter()
+        .filter(|assets| assets.name != MANIFEST_NAME && assets.name != SIGNATURE_NAME)
+        // 只选择能够安装的格式
+        .filter(|assets| PackageFormat::from_name(&assets.name).is_some());
+    let tokens = |assets: &Assets| -> Vec<String> {
+        assets.name.to_lowercase().split(['-', '.']).map(str::to_string).collect()
+    };
+    let has_any = |tokens: &[String], aliases: &[&str]| tokens.iter().any(|token| aliases.contains(&token.as_str()));
+
+    if let Some(assets) = packages.clone().find(|assets| {
+        let tokens = tokens(assets);
+        has_any(&tokens, os_aliases) && has_any(&tokens, arch_aliases)
+    }) {
+        return Some(assets);
+    }
+
+    if os != "windows" || arch != "x86_64" {
+        return None;
     }
+    let all_os: &[&str] = &["windows", "win64", "win", "win32", "macos", "darwin", "osx", "linux"];
+    packages.find(|assets| !has_any(&tokens(assets), all_os))
 }
 
-pub(crate) async fn download_file(release: Release, filepath: String, service_tx: UiRepaintSender) -> Result<(), Error> {
-    let assets = release.assets.get(0).ok_or(Error::msg("获取更新包失败，请重试"))?;
+// 解析 `Content-Range: bytes <start>-<end>/<total>` 中的起始位置
+fn content_range_start(value: &str) -> Option<u64> {
+    value.trim().strip_prefix("bytes ")?.split_once('-')?.0.trim().parse().ok()
+}
+
+fn find_asset<'a>(assets: &'a [Assets], name: &str) -> Option<&'a str> {
+    assets
+        .iter()
+        .find(|assets| assets.name == name)
+        .map(|assets| assets.browser_download_url.as_str())
+}
 
-    // 下载升级包
-    let client = reqwest::Client::new();
-    let response = client.get(&assets.browser_download_url)
-        .header("User-Agent", "wuthering-waves-gacha-record")
+async fn fetch_bytes(url: &str) -> Result<Vec<u8>, CoreError> {
+    let response = http::client().get(url)
+        .header("User-Agent", USER_AGENT)
         .send()
-        .await?;
+        .await?
+        .error_for_status()?;
+    Ok(response.bytes().await?.to_vec())
+}
 
-    let content_length = response.content_length().unwrap_or(0);
-    let mut downloaded = 0;
+// 解析 sha256sum 格式的清单：`<哈希>  <文件名>`，文件名前可能带有表示二进制模式的 `*`
+fn parse_manifest(manifest: &str) -> HashMap<String, String> {
+    manifest
+        .lines()
+        .filter_map(|line| {
+            let (hash, name) = line.trim().split_once(char::is_whitespace)?;
+            let name = name.trim_start().trim_start_matches('*');
+            Some((name.to_string(), hash.to_lowercase()))
+        })
+        .collect()
+}
 
-    let mut file = tokio::fs::File::create(format!(r#"{}\{}"#, filepath, assets.name)).await?;
+/// 使用 Ed25519 公钥校验清单的分离签名，签名及公钥均为十六进制文本
+fn verify_signature(manifest: &[u8], signature: &[u8], public_key: &str) -> Result<(), CoreError> {
+    let public_key = hex::decode(public_key.trim())
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+        .ok_or_else(|| CoreError::Config(t!("error.detail.invalid_public_key")))?;
 
-    let mut stream = response.bytes_stream();
+    let signature = hex::decode(String::from_utf8_lossy(signature).trim())
+        .ok()
+        .and_then(|bytes| Signature::from_slice(&bytes).ok())
+        .ok_or(CoreError::UpdateSignatureInvalid)?;
 
-    let limit = Ratelimiter::builder(1).period(Duration::from_millis(200)).build()?;
-    while let Some(item) = stream.next().await {
-        let chunk = item?;
-        let len = chunk.len();
-        file.write_all(&chunk).await?;
-        downloaded += len;
+    public_key
+        .verify_strict(manifest, &signature)
+        .map_err(|_| CoreError::UpdateSignatureInvalid)
+}
 
-        let percent = if content_length > 0 {
-            (downloaded as f64 / content_length as f64) * 100.0
-        } else {
-            0.0
-        };
+#[test]
+fn select_update_test() {
+    let release = |tag_name: &str, prerelease: bool| Release {
+        name: tag_name.to_string(),
+        tag_name: tag_name.to_string(),
+        body: String::new(),
+        assets: vec![],
+        prerelease,
+        draft: false,
+        html_url: String::new(),
+    };
+    let releases = vec![
+        release("v0.6.0", false),
+        release("release", false),
+        release("v0.7.1", false),
+        release("v0.8.0-beta.1", true),
+    ];
+    let current = Version::parse("0.7.0").unwrap();
 
-        if let Ok(_) = limit.try_wait() {
-            let _ = service_tx.send(Normal(format!("下载中... {:.2}%", percent)));
+    let stable = UpdateConfig::default();
+    assert_eq!(select_update(releases.clone(), &current, &stable).unwrap().tag_name, "v0.7.1");
+
+    let beta = UpdateConfig { channel: UpdateChannel::Beta, ..Default::default() };
+    assert_eq!(select_update(releases.clone(), &current, &beta).unwrap().tag_name, "v0.8.0-beta.1");
+
+    let skipped = UpdateConfig { skipped_version: "v0.7.1".to_string(), ..Default::default() };
+    assert!(select_update(releases.clone(), &current, &skipped).is_none());
+
+    // 旧版本不视为更新
+    assert!(select_update(releases, &Version::parse("1.0.0").unwrap(), &beta).is_none());
+}
+
+#[test]
+fn update_source_resolve_test() {
+    let url = "https://github.com/a/b/releases/download/v1.0.0/app.zip";
+    assert_eq!(UpdateSource::GitHub.resolve(url), url);
+    assert_eq!(
+        UpdateSource::Mirror("https://mirror.example.com/{url}".to_string()).resolve(url),
+        format!("https://mirror.example.com/{}", url)
+    );
+    assert_eq!(
+        UpdateSource::Mirror("https://mirror.example.com/".to_string()).resolve(url),
+        format!("https://mirror.example.com/{}", url)
+    );
+    assert_eq!(UpdateSource::Custom("https://example.com/releases.json".to_string()).resolve(url), url);
+}
+
+#[test]
+fn select_asset_test() {
+    let assets = |name: &str| Assets { name: name.to_string(), browser_download_url: String::new() };
+    let list = vec![
+        assets(MANIFEST_NAME),
+        assets("wuthering-waves-gacha-record-v0.8.0-linux-x86_64.tar.gz"),
+        assets("wuthering-waves-gacha-record-v0.8.0-macos-arm64.zip"),
+        assets("wuthering-waves-gacha-record-v0.8.0-windows-x64.zip"),
+    ];
+
+    assert_eq!(select_asset(&list, "windows", "x86_64").unwrap().name, "wuthering-waves-gacha-record-v0.8.0-windows-x64.zip");
+    assert_eq!(select_asset(&list, "macos", "aarch64").unwrap().name, "wuthering-waves-gacha-record-v0.8.0-macos-arm64.zip");
+    assert!(select_asset(&list, "windows", "aarch64").is_none());
+
+    // 名称中不含平台信息的升级包视为 Windows x64 版本
+    let legacy = vec![
+        assets(SIGNATURE_NAME),
+        assets("wuthering-waves-gacha-record.zip"),
+    ];
+    assert_eq!(select_asset(&legacy, "windows", "x86_64").unwrap().name, "wuthering-waves-gacha-record.zip");
+    assert!(select_asset(&legacy, "linux", "x86_64").is_none());
+    assert!(select_asset(&list[..2], "windows", "x86_64").is_none());
+
+    // 无法安装的格式不会被选中
+    let unsupported = vec![
+        assets("wuthering-waves-gacha-record-v0.8.0-linux-x86_64.AppImage"),
+        assets("wuthering-waves-gacha-record-v0.8.0-linux-x86_64.tar.gz"),
+    ];
+    assert_eq!(select_asset(&unsupported, "linux", "x86_64").unwrap().name, "wuthering-waves-gacha-record-v0.8.0-linux-x86_64.tar.gz");
+    assert!(select_asset(&unsupported[..1], "linux", "x86_64").is_none());
+}
+
+#[tokio::test]
+async fn download_verified_test() {
+    use ed25519_dalek::{Signer, SigningKey};
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    // 本地模拟发布服务，按请求路径返回对应内容
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let asset_name = format!("app-v9.0.0-{}-{}.zip", std::env::consts::OS, std::env::consts::ARCH);
+    let package = b"fake update package".to_vec();
+
+    let signing_key = SigningKey::from_bytes(&[7; 32]);
+    let public_key = hex::encode(signing_key.verifying_key().to_bytes());
+    let manifest = format!("{}  {}\n", hex::encode(Sha256::digest(&package)), asset_name);
+    let signature = hex::encode(signing_key.sign(manifest.as_bytes()).to_bytes());
+
+    let release_json = serde_json::json!({
+        "name": "v9.0.0",
+        "tag_name": "v9.0.0",
+        "body": "",
+        "assets": [
+            { "name": asset_name, "browser_download_url": format!("{}/asset", base) },
+            { "name": MANIFEST_NAME, "browser_download_url": format!("{}/manifest", base) },
+            { "name": SIGNATURE_NAME, "browser_download_url": format!("{}/signature", base) },
+        ],
+    });
+    let routes: Arc<HashMap<String, Vec<u8>>> = Arc::new(HashMap::from([
+        ("/releases/latest".to_string(), release_json.to_string().into_bytes()),
+        ("/asset".to_string(), package.clone()),
+        ("/tampered".to_string(), b"tampered package".to_vec()),
+        ("/resume-error".to_string(), package.clone()),
+        ("/resume-mismatch".to_string(), package.clone()),
+        ("/manifest".to_string(), manifest.into_bytes()),
+        ("/signature".to_string(), signature.into_bytes()),
+        ("/bad-signature".to_string(), hex::encode([0u8; 64]).into_bytes()),
+    ]));
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut buffer = [0; 2048];
+            let len = stream.read(&mut buffer).await.unwrap_or(0);
+            let request = String::from_utf8_lossy(&buffer[..len]);
+            let path = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+            // 支持 `Range: bytes=N-` 形式的断点续传请求
+            let start = request
+                .lines()
+                .find_map(|line| line.to_lowercase().strip_prefix("range: bytes=")?.strip_suffix('-')?.parse::<usize>().ok());
+            let (head, body) = match (routes.get(&path), start) {
+                // 续传请求出错
+                (Some(_), Some(_)) if path == "/resume-error" => (
+                    "HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+                    &[][..],
+                ),
+                // 返回的范围与请求不符
+                (Some(body), Some(_)) if path == "/resume-mismatch" => (
+                    format!(
+                        "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 0-{}/{}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        body.len() - 1, body.len(), body.len()
+                    ),
+                    &body[..],
+                ),
+                (Some(body), Some(start)) => (
+                    format!(
+                        "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {}-{}/{}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        start, body.len() - 1, body.len(), body.len() - start
+                    ),
+                    &body[start..],
+                ),
+                (Some(body), None) => (
+                    format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len()),
+                    &body[..],
+                ),
+                (None, _) => ("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(), &[][..]),
+            };
+            let _ = stream.write_all(head.as_bytes()).await;
+            let _ = stream.write_all(body).await;
         }
+    });
+
+    let release = check_update_from(&format!("{}/releases", base), &UpdateConfig::default())
+        .await
+        .unwrap()
+        .unwrap();
+    // 自建发布信息地址
+    let custom = UpdateConfig {
+        source: UpdateSource::Custom(format!("{}/releases/latest", base)),
+        ..Default::default()
+    };
+    let custom_release = check_update_from("http://127.0.0.1:1/unused", &custom).await.unwrap().unwrap();
+    assert_eq!(custom_release.tag_name, release.tag_name);
+
+    let (sender, _receiver) = tokio::sync::mpsc::unbounded_channel();
+    let service_tx = UiRepaintSender { sender, ctx: egui::Context::default() };
+    let dir = std::env::temp_dir().join(format!("wwgr-update-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let running = AtomicBool::new(false);
+    let part_path = dir.join(format!("{}.part", asset_name));
+
+    // 取消下载时保留已下载的部分
+    std::fs::write(&part_path, &package[..5]).unwrap();
+    let result = download_verified(&release, &dir, &public_key, &AtomicBool::new(true), &service_tx).await;
+    assert!(matches!(result, Err(CoreError::DownloadCancelled)));
+    assert_eq!(std::fs::read(&part_path).unwrap(), &package[..5]);
+
+    // 续传请求失败或返回的范围不符时不改动已下载的部分
+    for path in ["/resume-error", "/resume-mismatch"] {
+        let mut resume_failed = release.clone();
+        resume_failed.assets[0].browser_download_url = format!("{}{}", base, path);
+        assert!(download_verified(&resume_failed, &dir, &public_key, &running, &service_tx).await.is_err());
+        assert_eq!(std::fs::metadata(&part_path).unwrap().len(), 5);
     }
 
-    Ok(())
-}
\ No newline at end of file
+    // 从断点继续下载，校验通过后重命名
+    let file_path = download_verified(&release, &dir, &public_key, &running, &service_tx).await.unwrap();
+    assert_eq!(std::fs::read(&file_path).unwrap(), package);
+    assert!(!part_path.exists());
+    std::fs::remove_file(&file_path).unwrap();
+
+    // 升级包被篡改
+    let mut tampered = release.clone();
+    tampered.assets[0].browser_download_url = format!("{}/tampered", base);
+    let result = download_verified(&tampered, &dir, &public_key, &running, &service_tx).await;
+    assert!(matches!(result, Err(CoreError::UpdateChecksumMismatch { .. })));
+    assert!(!dir.join(&asset_name).exists());
+    assert!(!part_path.exists());
+
+    // 清单签名无效
+    let mut bad_signature = release.clone();
+    bad_signature.assets[2].browser_download_url = format!("{}/bad-signature", base);
+    let result = download_verified(&bad_signature, &dir, &public_key, &running, &service_tx).await;
+    assert!(matches!(result, Err(CoreError::UpdateSignatureInvalid)));
+
+    // 公钥不匹配
+    let other_key = hex::encode(SigningKey::from_bytes(&[8; 32]).verifying_key().to_bytes());
+    let result = download_verified(&release, &dir, &other_key, &running, &service_tx).await;
+    assert!(matches!(result, Err(CoreError::UpdateSignatureInvalid)));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -1,16 +1,18 @@
 use std::fs;
 use std::fs::OpenOptions;
 use std::io::{Read, Write};
-use anyhow::Error;
 use regex::Regex;
 use sysinfo::System;
 use tracing::info;
 use url::Url;
+use crate::core::account::{visible_player_ids, DATA_DIR};
+use crate::core::error::CoreError;
 use crate::core::gacha::RequestParam;
-use crate::core::message::MessageType::Normal;
+use crate::core::message::Event::Normal;
+use crate::i18n::t;
 use crate::view::main_view::UiRepaintSender;
 
-pub(crate) fn get_wuthering_waves_progress_path() -> anyhow::Result<String, Error> {
+pub(crate) fn get_wuthering_waves_progress_path() -> Result<String, CoreError> {
     let mut system = System::new();
     system.refresh_all();
 
@@ -45,7 +47,7 @@ pub(crate) fn get_wuthering_waves_progress_path() -> anyhow::Result<String, Erro
     }
 
     if log_path.is_empty() {
-        Err(Error::msg("未找到游戏进程"))
+        Err(CoreError::GameNotFound)
     } else {
         Ok(format!(r#"{}\Client.log"#, log_path).to_string())
     }
@@ -57,12 +59,13 @@ fn get_wuthering_waves_progress_path_test() {
     info!("{:?}", path);
 }
 
-pub(crate) fn get_param_from_logfile(player_id: String, server_sender: &UiRepaintSender) -> Result<(bool, RequestParam), Error> {
+pub(crate) fn get_param_from_logfile(player_id: String, server_sender: &UiRepaintSender) -> Result<(bool, RequestParam), CoreError> {
     // 从配置文件中获取历史 url
     let _ = fs::create_dir_all(format!("./data/{}", player_id));
-    if let Ok(mut file) = OpenOptions::new().read(true).open(format!("./data/{}/url_cache.txt", player_id)) {
+    let url_cache_path = format!("./data/{}/url_cache.txt", player_id);
+    if let Ok(mut file) = OpenOptions::new().read(true).open(&url_cache_path) {
         let mut buffer = String::new();
-        file.read_to_string(&mut buffer)?;
+        file.read_to_string(&mut buffer).map_err(|err| CoreError::io(&url_cache_path, err))?;
         if !buffer.is_empty() {
             let (oversea, param) = get_request_param(buffer)?;
             return Ok((oversea, param));
@@ -74,16 +77,17 @@ pub(crate) fn get_param_from_logfile(player_id: String, server_sender: &UiRepain
     // 从路径中截取文件名称用于展示
     let start_index = logfile_path.rfind("\\").unwrap_or_default() + 1;
     let (_, filename) = logfile_path.split_at(start_index);
-    let _ = server_sender.send(Normal(format!("正在从日志文件中获取卡池地址：{}", filename)));
+    let _ = server_sender.send(Normal(t!("status.reading_log", file = filename)));
 
     info!("解析到的日志：{}", filename);
     let mut file = OpenOptions::new()
         .read(true)
-        .open(logfile_path.clone())?;
+        .open(&logfile_path)
+        .map_err(|err| CoreError::io(&logfile_path, err))?;
 
     // 日志文件解密
     let mut buffer = vec![];
-    file.read_to_end(&mut buffer)?;
+    file.read_to_end(&mut buffer).map_err(|err| CoreError::io(&logfile_path, err))?;
     buffer = buffer.iter().map(|item| {
         if (item & 0x0F) % 2 == 1 {
             item ^ 0xA5
@@ -94,7 +98,8 @@ pub(crate) fn get_param_from_logfile(player_id: String, server_sender: &UiRepain
 
     let buffer = String::from_utf8_lossy(&buffer);
 
-    let regex = Regex::new(r#"https.*/aki/gacha/index.html#/record[?=&\w\-]+"#)?;
+    let regex = Regex::new(r#"https.*/aki/gacha/index.html#/record[?=&\w\-]+"#)
+        .map_err(|err| CoreError::Internal(err.to_string()))?;
     // TODO 性能优化
     // 匹配最近打开的 Url
     let mut url_vec = vec![];
@@ -114,24 +119,26 @@ pub(crate) fn get_param_from_logfile(player_id: String, server_sender: &UiRepain
 
         // 将获取到的抽卡页面 url 存入文件
         let _ = fs::create_dir_all(format!("./data/{}", param.player_id));
+        let url_cache_path = format!("./data/{}/url_cache.txt", param.player_id);
         let mut file = OpenOptions::new()
             .write(true)
             .create(true)
             .truncate(true)
-            .open(format!("./data/{}/url_cache.txt", param.player_id))?;
-        file.write_all(url.as_bytes())?;
+            .open(&url_cache_path)
+            .map_err(|err| CoreError::io(&url_cache_path, err))?;
+        file.write_all(url.as_bytes()).map_err(|err| CoreError::io(&url_cache_path, err))?;
 
         return Ok((oversea, param));
     }
 
 
-    Err(Error::msg("未打开过抽卡页面"))
+    Err(CoreError::UrlNotFound { log_file: filename.to_string() })
 }
 
 #[test]
 fn get_url_from_logfile_test() {
     use egui::Context;
-    let (tx, _) = std::sync::mpsc::channel();
+    let (tx, _) = tokio::sync::mpsc::unbounded_channel();
     let tx = UiRepaintSender {
         sender: tx,
         ctx: Context::default(),
@@ -141,20 +148,20 @@ fn get_url_from_logfile_test() {
 }
 
 // https://aki-gm-resources.aki-game.com/aki/gacha/index.html#/record?svr_id=***&player_id=***&lang=zh-Hans&gacha_id=100003&gacha_type=1&svr_area=cn&record_id=***&resources_id=***
-pub(crate) fn get_request_param(url: String) -> Result<(bool, RequestParam), Error> {
+pub(crate) fn get_request_param(url: String) -> Result<(bool, RequestParam), CoreError> {
     // 清除 url 中的 #
     let url = url.replace("#", "");
     let url = Url::parse(&*url)?;
 
     // 兼容国际服地址
-    let host = url.host_str().ok_or(Error::msg("抽卡地址错误"))?;
+    let host = url.host_str().ok_or(CoreError::InvalidUrl(url::ParseError::EmptyHost))?;
     // 国服：aki-gm-resources.aki-game.com
     // 国际服：aki-gm-resources-oversea.aki-game.net
     let oversea =
         match host {
             "aki-gm-resources-oversea.aki-game.net" => { true }
             "aki-gm-resources.aki-game.com" => { false }
-            _ => { return Err(Error::msg("未适配的服务器")); }
+            _ => { return Err(CoreError::UnsupportedHost { host: host.to_string() }); }
         };
 
     let param = url.query_pairs();
@@ -185,15 +192,17 @@ fn get_request_param_test() {
     println!("{:?}", param);
 }
 
-pub(crate) fn get_player_id_vec() -> Result<Vec<String>, Error> {
-    let _ = fs::create_dir_all("./data");
-    let data_dir = fs::read_dir("./data")?;
-    let mut player_id_vec = vec![];
-    for dir in data_dir.filter_map(Result::ok) {
-        if dir.metadata()?.is_dir() {
-            player_id_vec.push(dir.file_name().into_string().unwrap_or("数据异常".to_string()));
-        }
-    }
+#[test]
+fn get_request_param_unsupported_host_test() {
+    let url = "https://example.com/aki/gacha/index.html#/record?svr_id=***&player_id=***";
+    let result = get_request_param(url.to_string());
+
+    assert!(matches!(result, Err(CoreError::UnsupportedHost { host }) if host == "example.com"));
+}
+
+pub(crate) fn get_player_id_vec() -> Result<Vec<String>, CoreError> {
+    // 跳过回收站等内部目录、无法识别的目录及已隐藏的用户
+    let player_id_vec = visible_player_ids(DATA_DIR)?;
 
     info!("{:?}", player_id_vec);
     Ok(player_id_vec)
@@ -0,0 +1,60 @@
+use std::fs;
+use std::sync::Arc;
+use egui::FontData;
+use egui::FontFamily::Proportional;
+use tracing::info;
+use crate::i18n::Language;
+
+// 随程序打包的界面字体 得意黑，导出图片时同样使用
+pub(crate) const BUNDLED_FONT: &[u8] = include_bytes!("resource/fonts/SmileySans-Oblique.otf");
+
+// 后备字体，SmileySans 缺少部分汉字及日文假名时依次尝试
+// 拉丁字母由 egui 默认字体兜底
+pub(crate) const CJK_FALLBACK_FONTS: [&str; 6] = [
+    r#"C:\Windows\Fonts\msyh.ttc"#,
+    r#"C:\Windows\Fonts\meiryo.ttc"#,
+    "/System/Library/Fonts/PingFang.ttc",
+    "/System/Library/Fonts/ヒラギノ角ゴシック W3.ttc",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+];
+
+// 日文界面优先使用日文字形
+pub(crate) const JA_FALLBACK_FONTS: [&str; 3] = [
+    r#"C:\Windows\Fonts\YuGothM.ttc"#,
+    r#"C:\Windows\Fonts\meiryo.ttc"#,
+    "/System/Library/Fonts/ヒラギノ角ゴシック W3.ttc",
+];
+
+pub(crate) fn setup_fonts(ctx: &egui::Context, language: Language) {
+    let mut fonts = egui::FontDefinitions::default();
+
+    // 使用 得意黑 作为 UI 字体
+    fonts.font_data.insert(
+        "SmileySans".to_owned(),
+        Arc::from(FontData::from_static(BUNDLED_FONT)),
+    );
+
+    let proportional = fonts.families.entry(Proportional).or_default();
+    proportional.insert(0, "SmileySans".to_owned());
+
+    let fallback_fonts = if language == Language::Ja {
+        JA_FALLBACK_FONTS.iter().chain(CJK_FALLBACK_FONTS.iter())
+    } else {
+        CJK_FALLBACK_FONTS.iter().chain(JA_FALLBACK_FONTS.iter())
+    };
+    // 字体文件较大，只加载第一个可用的后备字体
+    if let Some((path, font)) = fallback_fonts
+        .filter_map(|path| fs::read(path).ok().map(|font| (path, font)))
+        .next()
+    {
+        info!("加载后备字体：{}", path);
+        proportional.push("CjkFallback".to_owned());
+        fonts.font_data.insert(
+            "CjkFallback".to_owned(),
+            Arc::from(FontData::from_owned(font)),
+        );
+    }
+
+    ctx.set_fonts(fonts);
+}
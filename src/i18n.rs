@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicU8, Ordering};
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+
+/// 界面语言
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub(crate) enum Language {
+    #[default]
+    #[serde(rename = "zh-Hans")]
+    ZhHans,
+    #[serde(rename = "en")]
+    En,
+    #[serde(rename = "ja")]
+    Ja,
+}
+
+impl Language {
+    pub(crate) const ALL: [Language; 3] = [Language::ZhHans, Language::En, Language::Ja];
+
+    // 语言代码，与抽卡接口的 lang 参数一致
+    pub(crate) fn code(&self) -> &'static str {
+        match self {
+            Language::ZhHans => "zh-Hans",
+            Language::En => "en",
+            Language::Ja => "ja",
+        }
+    }
+
+    // 语言名称使用该语言本身展示
+    pub(crate) fn display_name(&self) -> &'static str {
+        match self {
+            Language::ZhHans => "简体中文",
+            Language::En => "English",
+            Language::Ja => "日本語",
+        }
+    }
+
+    fn catalogue_source(&self) -> &'static str {
+        match self {
+            Language::ZhHans => include_str!("resource/i18n/zh-Hans.toml"),
+            Language::En => include_str!("resource/i18n/en.toml"),
+            Language::Ja => include_str!("resource/i18n/ja.toml"),
+        }
+    }
+}
+
+static LANGUAGE: AtomicU8 = AtomicU8::new(Language::ZhHans as u8);
+
+// 翻译目录，键为 `分组.名称` 形式
+static CATALOGUE: LazyLock<HashMap<Language, HashMap<String, String>>> = LazyLock::new(|| {
+    Language::ALL
+        .into_iter()
+        .map(|language| (language, parse_catalogue(language.catalogue_source())))
+        .collect()
+});
+
+fn parse_catalogue(source: &str) -> HashMap<String, String> {
+    let mut catalogue = HashMap::new();
+    if let Ok(table) = source.parse::<Table>() {
+        flatten(&mut catalogue, "", &table);
+    }
+    catalogue
+}
+
+fn flatten(catalogue: &mut HashMap<String, String>, prefix: &str, table: &Table) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match value {
+            Value::String(text) => {
+                catalogue.insert(key, text.clone());
+            }
+            Value::Table(table) => flatten(catalogue, &key, table),
+            _ => {}
+        }
+    }
+}
+
+pub(crate) fn set_language(language: Language) {
+    LANGUAGE.store(language as u8, Ordering::Relaxed);
+}
+
+pub(crate) fn language() -> Language {
+    Language::ALL
+        .into_iter()
+        .find(|language| *language as u8 == LANGUAGE.load(Ordering::Relaxed))
+        .unwrap_or_default()
+}
+
+/// 获取当前语言的文本，缺失时回退到简体中文，仍缺失则返回键本身
+pub(crate) fn tr(key: &str) -> String {
+    CATALOGUE
+        .get(&language())
+        .and_then(|catalogue| catalogue.get(key))
+        .or_else(|| CATALOGUE.get(&Language::ZhHans).and_then(|catalogue| catalogue.get(key)))
+        .cloned()
+        .unwrap_or_else(|| key.to_string())
+}
+
+/// 获取文本并替换其中的 `{name}` 占位符
+pub(crate) fn tr_args(key: &str, args: &[(&str, String)]) -> String {
+    let mut text = tr(key);
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), value);
+    }
+    text
+}
+
+/// `t!("key")` 或 `t!("key", name = value)`
+macro_rules! t {
+    ($key:expr) => {
+        $crate::i18n::tr($key)
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::tr_args($key, &[$((stringify!($name), $value.to_string())),+])
+    };
+}
+
+pub(crate) use t;
+
+// 卡池名称
+pub(crate) fn card_pool_name(card_pool_type: i32) -> String {
+    match card_pool_type {
+        1..=11 => t!(&format!("pool.{}", card_pool_type)),
+        _ => t!("pool.unknown"),
+    }
+}
+
+#[test]
+fn catalogue_complete_test() {
+    let base = &CATALOGUE[&Language::ZhHans];
+    assert!(!base.is_empty());
+
+    for language in Language::ALL {
+        let catalogue = &CATALOGUE[&language];
+        for key in base.keys() {
+            assert!(catalogue.contains_key(key), "{:?} 缺少翻译：{}", language, key);
+        }
+    }
+}
+
+#[test]
+fn error_catalogue_complete_test() {
+    use crate::core::error::CoreError;
+
+    let io = || std::io::Error::other("io");
+    let json = || serde_json::from_str::<i32>("").unwrap_err();
+    let text = || "text".to_string();
+    let errors = [
+        CoreError::GameNotFound,
+        CoreError::UrlNotFound { log_file: text() },
+        CoreError::UrlExpired { code: -1, message: text() },
+        CoreError::InvalidUrl(url::ParseError::EmptyHost),
+        CoreError::UnsupportedHost { host: text() },
+        CoreError::Network(reqwest::Client::new().get("not a url").build().unwrap_err()),
+        CoreError::corrupted(text(), json()),
+        CoreError::io(text(), io()),
+        CoreError::InvalidTime(text()),
+        CoreError::NoCache,
+        CoreError::UpdateAssetMissing,
+        CoreError::UpdateManifestMissing,
+        CoreError::UpdateSignatureInvalid,
+        CoreError::UpdateChecksumMismatch { asset: text(), expected: text(), actual: text() },
+        CoreError::UpdateArchiveInvalid { path: text(), detail: text() },
+        CoreError::UpdateSmokeCheckFailed { detail: text() },
+        CoreError::AccountMismatch { expected: text(), actual: text() },
+        CoreError::ExportFailed { path: text(), detail: text() },
+        CoreError::FetchCancelled,
+        CoreError::DownloadCancelled,
+        CoreError::DownloadResumeFailed { detail: text() },
+        CoreError::Config(text()),
+        CoreError::Internal(text()),
+    ];
+
+    // 每种错误在所有语言中都有提示及处理建议，且占位符均有对应参数
+    for language in Language::ALL {
+        let catalogue = &CATALOGUE[&language];
+        for err in errors.iter() {
+            let key = format!("error.{}", err.catalogue_key());
+            let hint = format!("{}_hint", key);
+            assert!(catalogue.contains_key(&hint), "{:?} 缺少翻译：{}", language, hint);
+            let mut message = catalogue.get(&key).unwrap_or_else(|| panic!("{:?} 缺少翻译：{}", language, key)).clone();
+            for (name, value) in err.catalogue_args() {
+                message = message.replace(&format!("{{{}}}", name), &value);
+            }
+            assert!(!message.contains('{'), "{:?} 的 {} 存在未替换的占位符", language, key);
+        }
+    }
+}
+
+#[test]
+fn tr_args_test() {
+    let text = parse_catalogue("[status]\nfetching_pool = \"正在获取卡池 {pool} 的数据\"");
+    assert_eq!(text["status.fetching_pool"], "正在获取卡池 {pool} 的数据");
+    assert_eq!(tr_args("status.fetching_pool", &[("pool", 3.to_string())]), "正在获取卡池 3 的数据");
+    assert_eq!(tr("not.exist"), "not.exist");
+}
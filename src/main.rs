@@ -1,6 +1,7 @@
 // hide console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use std::path::Path;
 use eframe::Renderer::Wgpu;
 use time::macros::format_description;
 use tracing::level_filters::LevelFilter;
@@ -8,10 +9,14 @@ use tracing::subscriber;
 use tracing_subscriber::fmt::time::LocalTime;
 use tracing_subscriber::Layer;
 use tracing_subscriber::layer::SubscriberExt;
+use crate::core::install::{cleanup_previous_install, SMOKE_CHECK_ARG};
 use crate::core::statistics::gacha_statistics;
-use crate::view::main_view::MainView;
+use crate::i18n::t;
+use crate::view::main_view::{Config, MainView};
 
 mod core;
+mod fonts;
+mod i18n;
 mod view;
 mod widgets;
 
@@ -19,6 +24,12 @@ pub(crate) const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[tokio::main]
 async fn main() -> eframe::Result {
+    // 安装更新后的自检，只输出版本号
+    if std::env::args().any(|arg| arg == SMOKE_CHECK_ARG) {
+        println!("{}", VERSION);
+        return Ok(());
+    }
+
     // 日志初始化
     let local_time = LocalTime::new(format_description!(
                 "[year]-[month]-[day] [hour]:[minute]:[second]"
@@ -36,22 +47,27 @@ async fn main() -> eframe::Result {
         rgba: icon.into_rgba8().into_raw(),
     };
 
+    // 清理上次更新留下的备份文件
+    if let Some(install_dir) = std::env::current_exe().ok().as_deref().and_then(Path::parent) {
+        cleanup_previous_install(install_dir);
+    }
+
+    let config = Config::load();
+    i18n::set_language(config.language);
+
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_resizable(false)
-            .with_maximize_button(false)
-            .with_inner_size([900.0, 520.0])
-            .with_icon(icon),
+        viewport: config.window.apply(egui::ViewportBuilder::default().with_icon(icon)),
         renderer: Wgpu,
-        centered: true,
+        // 未记录窗口位置时居中显示
+        centered: config.window.centered(),
         ..Default::default()
     };
 
     eframe::run_native(
-        format!("鸣潮抽卡记录工具 v{}", VERSION).as_str(),
+        t!("app.title", version = VERSION).as_str(),
         options,
         Box::new(|cc| {
-            Ok(Box::new(MainView::new(cc)))
+            Ok(Box::new(MainView::new(cc, config)))
         }),
     )
 }
\ No newline at end of file
@@ -1 +1,2 @@
-pub(crate) mod main_view;
\ No newline at end of file
+pub(crate) mod main_view;
+pub(crate) mod icon_cache;
@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+use std::path::Path;
+use egui::{ColorImage, TextureHandle, TextureOptions};
+use tracing::warn;
+use crate::core::resource::resource_icon_path;
+
+/// 按资源 ID 缓存已加载的图标纹理，不存在或无法解析的图标也会缓存，避免重复读取
+#[derive(Default)]
+pub(crate) struct IconCache {
+    textures: HashMap<i32, Option<TextureHandle>>,
+}
+
+impl IconCache {
+    pub(crate) fn get(&mut self, ctx: &egui::Context, resource_id: i32) -> Option<TextureHandle> {
+        self.textures
+            .entry(resource_id)
+            .or_insert_with(|| {
+                load_icon(&resource_icon_path(resource_id)).map(|image| ctx.load_texture(format!("icon_{}", resource_id), image, TextureOptions::LINEAR))
+            })
+            .clone()
+    }
+
+    // 图标包更新后重新加载
+    pub(crate) fn clear(&mut self) {
+        self.textures.clear();
+    }
+}
+
+fn load_icon(path: &Path) -> Option<ColorImage> {
+    if !path.exists() {
+        return None;
+    }
+    match image::open(path) {
+        Ok(image) => {
+            let image = image.into_rgba8();
+            let size = [image.width() as usize, image.height() as usize];
+            Some(ColorImage::from_rgba_unmultiplied(size, image.as_raw()))
+        }
+        Err(err) => {
+            warn!("图标 {} 加载失败：{}", path.display(), err);
+            None
+        }
+    }
+}
@@ -1,72 +1,307 @@
-use crate::core::message::MessageType;
-use crate::core::message::MessageType::{
-    CheckUpdate, DownloadFile, Gacha, NeedUpdate, Normal, Player, UpdateData, Warning,
-};
+use crate::core::account::{apply_account_action, list_accounts, AccountAction, AccountInfo};
+use crate::core::message::{Command, Event};
 use crate::core::statistics::{
-    gacha_statistics_from_cache, GachaStatistics, GachaStatisticsDataItem,
+    activity_statistics, aggregate_statistics_from_cache, compare_statistics_from_cache, ActivityStatistics, gacha_statistics_from_cache, AccountComparison,
+    AggregateStatistics, GachaStatistics, GachaStatisticsData, GachaStatisticsDataItem, Period, PoolRunStatistics, PeriodActivity, PoolComparison, StatisticsSummary,
+};
+use crate::core::error::CoreError;
+use crate::core::export::{export_summary, mask_uid, ExportFormat, ExportOptions};
+use crate::core::report::render_markdown;
+use crate::core::roster::{roster, ResourceKind, Roster};
+use crate::view::icon_cache::IconCache;
+use crate::core::gacha::{FetchPhase, FetchProgress};
+use crate::core::http;
+use crate::core::http::{NetworkConfig, RequestConfig};
+use crate::core::install::{install_update, relaunch};
+use crate::core::region::{DisplayTimeZone, Region};
+use crate::core::resource::{resource_info, resource_name, ICON_DIR};
+use crate::core::update::{
+    check_update, download_file, DownloadProgress, Release, UpdateChannel, UpdateConfig, UpdateSource,
 };
-use crate::core::update::{check_update, download_file, Release};
 use crate::core::util::get_player_id_vec;
+use crate::fonts::{setup_fonts, CJK_FALLBACK_FONTS, JA_FALLBACK_FONTS};
 use crate::gacha_statistics;
+use crate::i18n;
+use crate::i18n::{card_pool_name, t, Language};
+use crate::VERSION;
+use crate::widgets::calendar_heatmap::{heatmap_years, CalendarHeatmap, HeatmapCell};
 use crate::widgets::pie_chart::PieChart;
+use crate::widgets::pity_histogram::PityHistogram;
+use crate::widgets::pity_timeline::PityTimeline;
+use crate::widgets::stacked_bar_chart::StackedBarChart;
 use egui::FontFamily::Proportional;
-use egui::{CentralPanel, Color32, ComboBox, FontData, FontId, TextStyle, Ui};
+use egui::{CentralPanel, Color32, ComboBox, FontId, TextStyle, Ui};
 use egui_commonmark::{CommonMarkCache, CommonMarkViewer};
 use serde::{Deserialize, Serialize};
-use std::cmp::min;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
 use std::fs;
 use std::fs::OpenOptions;
 use std::io::Write;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::{Receiver, Sender};
-use std::sync::{mpsc, Arc};
-use std::sync::mpsc::SendError;
+use std::sync::{mpsc, Arc, Mutex};
 use std::time::Duration;
+use tokio::sync::mpsc::error::SendError;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinSet;
+use chrono::{DateTime, Local};
 use eframe::Frame;
 use egui::scroll_area::ScrollSource;
 use egui_theme_switch::global_theme_switch;
 use tracing::{error, info, warn};
 
-#[derive(Serialize, Deserialize)]
-struct Config {
+#[derive(Serialize, Deserialize, Default)]
+pub(crate) struct Config {
     dark_mode: bool,
     player_id_selected: String,
+    #[serde(default)]
+    request: RequestConfig,
+    #[serde(default)]
+    pub(crate) language: Language,
+    #[serde(default)]
+    time_zone: DisplayTimeZone,
+    #[serde(default)]
+    update: UpdateConfig,
+    #[serde(default)]
+    network: NetworkConfig,
+    // 卡池统计图以环形图显示
+    #[serde(default)]
+    donut_chart: bool,
+    #[serde(default)]
+    pub(crate) window: WindowConfig,
+    // 在角色及武器列表中显示本地图标包中的图标
+    #[serde(default)]
+    show_icons: bool,
+}
+
+/// 窗口大小、位置及界面缩放，尺寸均不含界面缩放
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub(crate) struct WindowConfig {
+    width: f32,
+    height: f32,
+    // 窗口左上角位置，未记录时居中显示
+    position: Option<[f32; 2]>,
+    maximized: bool,
+    ui_scale: f32,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            // 默认宽度可放下三列卡池卡片
+            width: 940.0,
+            height: 560.0,
+            position: None,
+            maximized: false,
+            ui_scale: 1.0,
+        }
+    }
+}
+
+impl WindowConfig {
+    // 窗口的最小尺寸，保证至少能放下一列卡池卡片
+    const MIN_SIZE: [f32; 2] = [640.0, 400.0];
+    // 设置中可选的界面缩放比例
+    const UI_SCALES: [f32; 7] = [0.75, 1.0, 1.25, 1.5, 1.75, 2.0, 2.5];
+
+    /// 按记录的窗口状态设置视口
+    pub(crate) fn apply(&self, viewport: egui::ViewportBuilder) -> egui::ViewportBuilder {
+        let viewport = viewport
+            .with_resizable(true)
+            .with_min_inner_size(Self::MIN_SIZE)
+            .with_inner_size([self.width.max(Self::MIN_SIZE[0]), self.height.max(Self::MIN_SIZE[1])])
+            .with_maximized(self.maximized);
+        match self.position {
+            Some(position) => viewport.with_position(position),
+            None => viewport,
+        }
+    }
+
+    pub(crate) fn centered(&self) -> bool {
+        self.position.is_none()
+    }
+
+    // 记录当前的窗口状态，最大化或最小化时保留原有的大小及位置
+    fn track(&mut self, ctx: &egui::Context) {
+        let zoom_factor = ctx.zoom_factor();
+        self.ui_scale = zoom_factor;
+        ctx.input(|input| {
+            let viewport = input.viewport();
+            if viewport.minimized == Some(true) || viewport.fullscreen == Some(true) {
+                return;
+            }
+            self.maximized = viewport.maximized == Some(true);
+            if self.maximized {
+                return;
+            }
+            if let Some(inner_rect) = viewport.inner_rect {
+                self.width = inner_rect.width() * zoom_factor;
+                self.height = inner_rect.height() * zoom_factor;
+            }
+            if let Some(outer_rect) = viewport.outer_rect {
+                self.position = Some([outer_rect.min.x * zoom_factor, outer_rect.min.y * zoom_factor]);
+            }
+        });
+    }
+}
+
+impl Config {
+    // 读取用户配置，读取失败时使用默认配置
+    pub(crate) fn load() -> Self {
+        fs::read_to_string("./data/config.toml")
+            .ok()
+            .and_then(|config| toml::from_str::<Config>(config.as_str()).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        let _ = fs::create_dir_all("./data");
+        if let Ok(config_str) = toml::to_string(self)
+            && let Ok(mut file) = OpenOptions::new()
+                .write(true)
+                .truncate(true)
+                .create(true)
+                .open("./data/config.toml")
+        {
+            let _ = file.write_all(config_str.as_bytes());
+        }
+    }
 }
 
 pub(crate) struct MainView {
-    view_tx: Sender<MessageType>,
-    view_rx: Receiver<MessageType>,
-    on_exit: Arc<AtomicBool>,
+    view_tx: UnboundedSender<Command>,
+    view_rx: UnboundedReceiver<Event>,
     dark_mode: bool,
+    request_config: RequestConfig,
+    language: Language,
+    time_zone: DisplayTimeZone,
+    update_config: UpdateConfig,
+    network_config: NetworkConfig,
+    donut_chart: bool,
+    window: WindowConfig,
+    // 导出摘要时是否隐藏 UID
+    export_mask_uid: bool,
 
     gacha_statistics: GachaStatistics,
     gacha_statistic_view_vec: Vec<GachaStatisticsView>,
+    // 各卡池统计图中选中的星级，对应统计图的数据下标
+    pool_selection: HashMap<i32, usize>,
     player_id_vec: Vec<String>,
     player_id_selected: String,
     player_id_last_selected: String,
+    accounts: Vec<AccountInfo>,
+    // 用户管理页面中输入的新抽卡链接
+    relink_urls: HashMap<String, String>,
+    // 等待确认删除的用户
+    pending_delete: Option<String>,
+    // 参与汇总统计的用户
+    aggregate_selected: BTreeSet<String>,
+    aggregate: AggregateStatistics,
+    // 对比的两个用户
+    compare_left: String,
+    compare_right: String,
+    comparison: Option<AccountComparison>,
+    activity: Option<ActivityStatistics>,
+    // 已请求汇总的用户及时区，与当前选择不一致时重新汇总
+    activity_requested: Option<(String, DisplayTimeZone)>,
+    activity_year: i32,
+    // 柱状图的汇总周期
+    activity_period: Period,
+    roster: Option<Roster>,
+    // 已请求统计的用户，与当前选择不一致时重新统计
+    roster_requested: Option<String>,
+    // 列表筛选条件，为空时不筛选
+    roster_kind: Option<ResourceKind>,
+    roster_quality: Option<i32>,
+    roster_search: String,
+    show_icons: bool,
+    icon_cache: IconCache,
     message: Message,
+    // 消息历史，按时间顺序排列
+    message_log: VecDeque<Message>,
+    show_message_log: bool,
     update_info: Option<Release>,
+    // 更新包下载中时的进度
+    download_progress: Option<DownloadProgress>,
+    // 数据刷新中时的进度
+    // 各用户正在进行的刷新进度，新增用户时键为空
+    fetch_progress: HashMap<String, FetchProgress>,
+    // 已下载并通过校验的更新包
+    downloaded_update: Option<PathBuf>,
+    installing: bool,
     view: View,
 
     markdown_cache: CommonMarkCache,
 }
 
-#[derive(Default)]
+// 消息级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Severity {
+    #[default]
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    fn color(&self, ui: &Ui) -> Color32 {
+        match self {
+            Severity::Info => ui.visuals().text_color(),
+            Severity::Warning => Color32::from_rgb(232, 176, 4),
+            Severity::Error => Color32::from_rgb(220, 80, 60),
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Severity::Info => t!("label.severity_info"),
+            Severity::Warning => t!("label.severity_warning"),
+            Severity::Error => t!("label.severity_error"),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
 struct Message {
-    success: bool,
+    severity: Severity,
     message: String,
+    // 出错时给出的处理建议
+    suggestion: String,
+    time: DateTime<Local>,
+}
+
+impl Message {
+    fn new(severity: Severity, message: String, suggestion: String) -> Self {
+        Self { severity, message, suggestion, time: Local::now() }
+    }
+
+    fn info(message: String) -> Self {
+        Self::new(Severity::Info, message, String::new())
+    }
+
+    fn warning(message: String) -> Self {
+        Self::new(Severity::Warning, message, String::new())
+    }
+
+    fn error(err: &CoreError) -> Self {
+        let (message, suggestion) = err.describe();
+        Self::new(Severity::Error, message, suggestion)
+    }
 }
 
-/// mpsc::Sender 包装，发送消息时刷新 UI
+// 消息历史最多保留的条数
+const MESSAGE_LOG_LIMIT: usize = 500;
+
+/// 事件通道的发送端包装，发送事件时刷新 UI
 #[derive(Clone)]
 pub struct UiRepaintSender {
-    pub sender: Sender<MessageType>,
+    pub sender: UnboundedSender<Event>,
     pub ctx: egui::Context
 }
 
 impl UiRepaintSender {
-    pub fn send(&self, message: MessageType) -> Result<(), SendError<MessageType>> {
+    pub fn send(&self, message: Event) -> Result<(), SendError<Event>> {
         let result = self.sender.send(message);
         // 刷新 UI
         self.ctx.request_repaint();
@@ -77,76 +312,114 @@ impl UiRepaintSender {
 enum View {
     Home,
     Update,
+    Settings,
+    Accounts,
+    Aggregate,
+    Compare,
+    Activity,
+    // 单个卡池的详情
+    PoolDetail(i32),
+    Roster,
 }
 
 impl MainView {
-    pub(crate) fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        // 初始化数据处理线程
-        // 服务发送 / 视图接收 通道
-        let (service_tx, view_rx) = mpsc::channel();
+    pub(crate) fn new(cc: &eframe::CreationContext<'_>, config: Config) -> Self {
+        // 初始化后台服务
+        // 服务发送 / 视图接收 事件通道
+        let (service_tx, view_rx) = unbounded_channel();
         let service_tx = UiRepaintSender {
             sender: service_tx,
             ctx: cc.egui_ctx.clone(),
         };
 
-        // 视图发送 / 服务接收 通道
-        let (view_tx, service_rx) = mpsc::channel();
-
-        let on_exit_flag = Arc::new(AtomicBool::new(false));
+        // 视图发送 / 服务接收 指令通道
+        let (view_tx, command_rx) = unbounded_channel();
 
-        start_data_flush_thread(Arc::clone(&on_exit_flag), service_tx, service_rx);
+        let Config {
+            dark_mode,
+            player_id_selected,
+            request: request_config,
+            language,
+            time_zone,
+            update: update_config,
+            network: network_config,
+            donut_chart,
+            window,
+            show_icons,
+        } = config;
+        i18n::set_language(language);
 
-        let _ = view_tx.send(UpdateData(true, "".to_string()));
-        let _ = view_tx.send(CheckUpdate);
+        let mut message = Message::default();
+        if let Err(err) = http::configure(&network_config) {
+            error!("网络设置无效：{}", err);
+            message = Message::error(&err);
+        }
 
-        let mut dark_mode = false;
-        let mut player_id_selected = String::default();
+        start_service(request_config.clone(), service_tx, command_rx);
 
-        // 读取用户配置
-        if let Ok(config) = fs::read_to_string("./data/config.toml") {
-            if let Ok(config) = toml::from_str::<Config>(config.as_str()) {
-                dark_mode = config.dark_mode;
-                player_id_selected = config.player_id_selected;
-            }
+        let _ = view_tx.send(Command::UpdateData(true, "".to_string()));
+        let _ = view_tx.send(Command::LoadAccounts);
+        if network_config.auto_check_update {
+            let _ = view_tx.send(Command::CheckUpdate(update_config.clone()));
         }
 
         // 样式配置
-        setup_custom_style(&cc.egui_ctx, dark_mode);
+        setup_custom_style(&cc.egui_ctx, dark_mode, language);
+        cc.egui_ctx.set_zoom_factor(window.ui_scale.clamp(WindowConfig::UI_SCALES[0], WindowConfig::UI_SCALES[WindowConfig::UI_SCALES.len() - 1]));
 
         Self {
             view_tx,
             view_rx,
-            on_exit: on_exit_flag,
             dark_mode,
+            request_config,
+            language,
+            time_zone,
+            update_config,
+            network_config,
+            donut_chart,
+            window,
+            export_mask_uid: true,
             gacha_statistics: GachaStatistics::new(),
             gacha_statistic_view_vec: vec![],
+            pool_selection: HashMap::new(),
             player_id_vec: vec![],
             player_id_last_selected: String::default(),
             player_id_selected,
-            message: Message::default(),
+            accounts: vec![],
+            relink_urls: HashMap::new(),
+            pending_delete: None,
+            aggregate_selected: BTreeSet::new(),
+            aggregate: AggregateStatistics::default(),
+            compare_left: String::new(),
+            compare_right: String::new(),
+            comparison: None,
+            activity: None,
+            activity_requested: None,
+            activity_year: 0,
+            activity_period: Period::Month,
+            roster: None,
+            roster_requested: None,
+            roster_kind: None,
+            roster_quality: None,
+            roster_search: String::new(),
+            show_icons,
+            icon_cache: IconCache::default(),
+            message_log: (!message.message.is_empty()).then(|| message.clone()).into_iter().collect(),
+            show_message_log: false,
+            message,
             update_info: None,
+            download_progress: None,
+            fetch_progress: HashMap::new(),
+            downloaded_update: None,
+            installing: false,
             view: View::Home,
             markdown_cache: CommonMarkCache::default(),
         }
     }
 }
 
-fn setup_custom_style(ctx: &egui::Context, _dark_mode: bool) {
-    let mut fonts = egui::FontDefinitions::default();
-
-    // 使用 得意黑 作为 UI 字体
-    fonts.font_data.insert(
-        "SmileySans".to_owned(),
-        Arc::from(FontData::from_static(include_bytes!("../resource/fonts/SmileySans-Oblique.otf"))),
-    );
-
-    fonts
-        .families
-        .get_mut(&Proportional)
-        .unwrap()
-        .insert(0, "SmileySans".to_owned());
-
-    ctx.set_fonts(fonts);
+fn setup_custom_style(ctx: &egui::Context, _dark_mode: bool, language: Language) {
+    setup_fonts(ctx, language);
 
     // 设置字体默认样式
     let text_styles: BTreeMap<TextStyle, FontId> = [
@@ -163,158 +436,383 @@ fn setup_custom_style(ctx: &egui::Context, _dark_mode: bool) {
     });
 }
 
-fn start_data_flush_thread(
-    on_exit_flag_clone: Arc<AtomicBool>,
+// 后台服务，每条指令对应的耗时任务在 JoinSet 中并行执行
+fn start_service(
+    mut request_config: RequestConfig,
     service_tx: UiRepaintSender,
-    service_rx: Receiver<MessageType>,
+    mut command_rx: UnboundedReceiver<Command>,
 ) {
     tokio::spawn(async move {
+        let mut jobs = JoinSet::new();
+        let mut download_cancel: Option<Arc<AtomicBool>> = None;
+        // 正在刷新的用户及其取消标记
+        let running_fetches: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>> = Arc::default();
+        // 从服务器获取数据时持有，新用户的 ID 在读取抽卡链接后才能确定，因此所有用户的获取依次进行
+        let fetch_lock: Arc<tokio::sync::Mutex<()>> = Arc::default();
+
         loop {
-            if on_exit_flag_clone.load(Ordering::Relaxed) {
-                info!("应用退出，停止后台线程");
+            let command = tokio::select! {
+                command = command_rx.recv() => command,
+                // 回收已结束的任务
+                Some(result) = jobs.join_next(), if !jobs.is_empty() => {
+                    if let Err(err) = result {
+                        error!("后台任务异常退出：{}", err);
+                    }
+                    continue;
+                }
+            };
+            // 视图已释放，不再有新的指令
+            let Some(command) = command else {
                 break;
-            }
+            };
 
-            if let Ok(message) = service_rx.recv_timeout(Duration::from_secs(1)) {
-                match message {
-                    CheckUpdate => {
+            match command {
+                Command::CheckUpdate(update_config) => {
+                    let service_tx = service_tx.clone();
+                    jobs.spawn(async move {
                         info!("检查应用更新");
-                        if let Ok(update_info) = check_update().await {
-                            info!("程序有更新");
-                            let _ = service_tx.send(NeedUpdate(update_info));
-                        } else {
-                            info!("当前已是最新版本");
-                        }
-                    }
-                    DownloadFile(release, filepath) => {
-                        let _ = service_tx.send(Normal("正在连接服务器...".to_string()));
-                        match download_file(release, filepath, service_tx.clone()).await {
-                            Ok(_) => {
-                                info!("更新包下载完毕");
-                                let _ = service_tx.send(Normal("下载完毕 100%".to_string()));
+                        match check_update(&update_config).await {
+                            Ok(Some(update_info)) => {
+                                info!("程序有更新");
+                                let _ = service_tx.send(Event::NeedUpdate(Box::new(update_info)));
+                            }
+                            Ok(None) => {
+                                info!("当前已是最新版本");
                             }
                             Err(err) => {
-                                let _ = service_tx.send(Warning(format!("下载失败：{}", err)));
+                                warn!("检查更新失败：{}", err);
                             }
                         }
+                    });
+                }
+                Command::DownloadFile(release, filepath) => {
+                    let _ = service_tx.send(Event::Normal(t!("status.connecting")));
+                    let cancel = Arc::new(AtomicBool::new(false));
+                    download_cancel = Some(Arc::clone(&cancel));
+                    let service_tx = service_tx.clone();
+                    jobs.spawn(async move {
+                        let result = download_file(*release, filepath, cancel, service_tx.clone()).await;
+                        match &result {
+                            Ok(path) => info!("更新包下载完毕：{}", path.display()),
+                            Err(err) => error!("更新包下载失败：{}", err),
+                        }
+                        let _ = service_tx.send(Event::DownloadFinished(result));
+                    });
+                }
+                Command::CancelDownload => {
+                    if let Some(cancel) = download_cancel.take() {
+                        info!("取消下载更新包");
+                        cancel.store(true, Ordering::Relaxed);
                     }
-                    UpdateData(cache, mut player_id) => {
-                        let _ = service_tx.send(Normal("加载中...".to_string()));
-                        if cache {
-                            // 从缓存中获取数据
-                            if let Ok(user_vec) = get_player_id_vec() {
-                                if !user_vec.is_empty() {
-                                    if player_id.is_empty() {
-                                        player_id = user_vec[0].clone();
-                                        let _ = service_tx.send(Player(user_vec));
-                                    }
-
-                                    // 第一次加载时尝试读缓存文件中的统计内容，加快首屏加载速度
-                                    match gacha_statistics_from_cache(player_id.clone()) {
-                                        Ok(gacha_statistics_data) => {
-                                            let _ = service_tx
-                                                .send(Gacha((player_id, gacha_statistics_data)));
-                                            let _ = service_tx.send(Normal(
-                                                "当前展示的是该用户最后一次获取的数据".to_string(),
-                                            ));
-                                            info!("刷新统计图");
-                                            continue;
-                                        }
-                                        Err(err) => {
-                                            let _ = service_tx.send(Warning(
-                                                "无缓存，正在尝试从服务器获取".to_string(),
-                                            ));
-                                            info!("无缓存：{}", err);
-                                        }
-                                    }
-                                }
+                }
+                Command::InstallUpdate(package, version) => {
+                    let _ = service_tx.send(Event::Normal(t!("status.installing")));
+                    let service_tx = service_tx.clone();
+                    jobs.spawn(async move {
+                        let result = blocking(move || {
+                            let exe = std::env::current_exe()
+                                .map_err(|err| CoreError::io("current_exe", err))?;
+                            let install_dir = exe.parent().unwrap_or(&exe);
+                            let exe_name = exe.file_name().unwrap_or_default().to_string_lossy();
+                            install_update(&package, install_dir, &exe_name, &version)
+                        })
+                        .await;
+                        if let Err(err) = &result {
+                            error!("更新安装失败：{}", err);
+                        }
+                        let _ = service_tx.send(Event::UpdateInstalled(result));
+                    });
+                }
+                Command::UpdateData(cache, player_id) => {
+                    // 同一用户同时只允许一个刷新任务
+                    let cancel = Arc::new(AtomicBool::new(false));
+                    match running_fetches.lock() {
+                        Ok(mut running) => {
+                            if running.contains_key(&player_id) {
+                                info!("用户 {} 的数据正在刷新，忽略本次请求", player_id);
+                                let _ = service_tx.send(Event::Warning(t!("status.refresh_running")));
+                                continue;
                             }
-
-                            let _ = service_tx
-                                .send(Warning("首次使用，正在尝试从服务器获取".to_string()));
+                            running.insert(player_id.clone(), Arc::clone(&cancel));
                         }
+                        Err(_) => continue,
+                    }
 
-                        // 从服务器获取抽卡数据
-                        match gacha_statistics(player_id, &service_tx).await {
-                            Ok(gacha_statistics_data) => {
-                                let _ = service_tx.send(Gacha(gacha_statistics_data));
-                                let _ = service_tx.send(Normal("获取完毕".to_string()));
-                                info!("刷新统计图");
-
-                                // 刷新当前保存数据的用户列表
-                                if let Ok(user_vec) = get_player_id_vec() {
-                                    let _ = service_tx.send(Player(user_vec));
-                                }
-                            }
-                            Err(err) => {
-                                let _ = service_tx
-                                    .send(Warning(format!("抽卡数据统计失败，失败原因：{}", err)));
-                                error!("抽卡数据统计失败：{}", err);
-                            }
+                    let running_fetches = Arc::clone(&running_fetches);
+                    let fetch_lock = Arc::clone(&fetch_lock);
+                    let request_config = request_config.clone();
+                    let service_tx = service_tx.clone();
+                    jobs.spawn(async move {
+                        let fetched = update_data(cache, player_id.clone(), &request_config, &fetch_lock, &cancel, &service_tx).await;
+                        if let Ok(mut running) = running_fetches.lock() {
+                            running.remove(&player_id);
+                        }
+                        // 只读取缓存时没有刷新进度需要结束
+                        if let Some(fetched) = fetched {
+                            let _ = service_tx.send(Event::FetchFinished(fetched));
+                        }
+                    });
+                }
+                Command::CancelFetch => {
+                    cancel_fetches(&running_fetches);
+                }
+                Command::UpdateRequestConfig(config) => {
+                    info!("更新请求配置：{:?}", config);
+                    request_config = config;
+                }
+                Command::LoadAccounts => {
+                    let service_tx = service_tx.clone();
+                    jobs.spawn(async move {
+                        send_accounts(&service_tx).await;
+                    });
+                }
+                Command::LoadAggregate(player_ids) => {
+                    let service_tx = service_tx.clone();
+                    jobs.spawn(async move {
+                        run_blocking(&service_tx, move || aggregate_statistics_from_cache(&player_ids), Event::Aggregate).await;
+                    });
+                }
+                Command::LoadComparison(left_id, right_id) => {
+                    let service_tx = service_tx.clone();
+                    jobs.spawn(async move {
+                        run_blocking(&service_tx, move || compare_statistics_from_cache(left_id, right_id), Event::Comparison).await;
+                    });
+                }
+                Command::LoadRoster(player_id) => {
+                    let service_tx = service_tx.clone();
+                    jobs.spawn(async move {
+                        run_blocking(&service_tx, move || roster(player_id), Event::Roster).await;
+                    });
+                }
+                Command::LoadActivity(player_id, time_zone) => {
+                    let service_tx = service_tx.clone();
+                    jobs.spawn(async move {
+                        run_blocking(&service_tx, move || activity_statistics(player_id, time_zone), Event::Activity).await;
+                    });
+                }
+                Command::UpdateAccount(player_id, action) => {
+                    info!("用户 {} 执行操作：{:?}", player_id, action);
+                    let service_tx = service_tx.clone();
+                    jobs.spawn(async move {
+                        let id = player_id.clone();
+                        run_blocking(
+                            &service_tx,
+                            move || apply_account_action(&id, action),
+                            |_| Event::Normal(t!("status.account_updated", uid = player_id)),
+                        )
+                        .await;
+                        // 隐藏或删除用户后同步更新用户列表
+                        if let Ok(user_vec) = get_player_id_vec() {
+                            let _ = service_tx.send(Event::Player(user_vec));
                         }
+                        send_accounts(&service_tx).await;
+                    });
+                }
+                Command::ExportSummary(player_id, format, options, path) => {
+                    let service_tx = service_tx.clone();
+                    jobs.spawn(async move {
+                        let path_text = path.display().to_string();
+                        let export = move || {
+                            let statistics = gacha_statistics_from_cache(player_id.clone())?;
+                            let fallback_fonts: Vec<&str> = CJK_FALLBACK_FONTS.iter().chain(JA_FALLBACK_FONTS.iter()).copied().collect();
+                            export_summary(&player_id, &statistics, format, options, &path, &fallback_fonts)
+                        };
+                        run_blocking(&service_tx, export, |_| Event::Normal(t!("status.export_done", path = path_text))).await;
+                    });
+                }
+                Command::Shutdown(ack) => {
+                    info!("应用退出，等待后台任务结束");
+                    // 取消后的任务会在写入数据前退出，不会留下不完整的文件
+                    cancel_fetches(&running_fetches);
+                    if let Some(cancel) = download_cancel.take() {
+                        cancel.store(true, Ordering::Relaxed);
                     }
-                    _ => {
-                        warn!("接收到了错误的消息");
+                    let drained = tokio::time::timeout(SHUTDOWN_TIMEOUT, async {
+                        while jobs.join_next().await.is_some() {}
+                    })
+                    .await;
+                    if drained.is_err() {
+                        warn!("后台任务未能在 {} 秒内结束", SHUTDOWN_TIMEOUT.as_secs());
+                        jobs.abort_all();
                     }
+                    let _ = ack.send(());
+                    break;
                 }
             }
         }
+
+        info!("后台服务已停止");
     });
 }
 
-impl eframe::App for MainView {
-    fn ui(&mut self, ui: &mut Ui, _frame: &mut Frame) {
-        if let Ok(message) = self.view_rx.try_recv() {
-            match message {
-                Normal(message) => {
-                    self.message = Message {
-                        success: true,
-                        message,
-                    }
-                }
-                Warning(message) => {
-                    self.message = Message {
-                        success: false,
-                        message,
-                    }
-                }
-                Gacha((player_id, gacha_statistic)) => {
-                    self.player_id_selected = player_id.clone();
-                    self.player_id_last_selected = player_id;
-                    self.gacha_statistics = gacha_statistic;
-                }
-                Player(player_id_vec) => {
-                    if !player_id_vec.is_empty() {
-                        for player_id in player_id_vec.clone() {
-                            if !self.player_id_vec.contains(&player_id) {
-                                self.player_id_selected = player_id.clone();
-                                self.player_id_last_selected = player_id;
-                            }
-                        }
-                    }
+// 退出时等待后台任务结束的最长时间
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+// 读取全部用户信息并发送给视图，需要解析每个用户的数据文件，放在阻塞线程中执行
+async fn send_accounts(service_tx: &UiRepaintSender) {
+    run_blocking(service_tx, list_accounts, Event::Accounts).await;
+}
+
+// 在阻塞线程中执行任务，线程异常退出视为内部错误
+async fn blocking<T, F>(task: F) -> Result<T, CoreError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, CoreError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(task)
+        .await
+        .unwrap_or_else(|err| Err(CoreError::Internal(err.to_string())))
+}
+
+// 在阻塞线程中执行任务，成功时发送 on_ok 生成的事件，失败时发送错误
+async fn run_blocking<T, F>(service_tx: &UiRepaintSender, task: F, on_ok: impl FnOnce(T) -> Event)
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, CoreError> + Send + 'static,
+{
+    match blocking(task).await {
+        Ok(value) => {
+            let _ = service_tx.send(on_ok(value));
+        }
+        Err(err) => {
+            error!("后台任务失败：{}", err);
+            let _ = service_tx.send(Event::Error(err));
+        }
+    }
+}
 
-                    self.player_id_vec = player_id_vec;
+fn cancel_fetches(running_fetches: &Mutex<HashMap<String, Arc<AtomicBool>>>) {
+    if let Ok(running) = running_fetches.lock() {
+        for (player_id, cancel) in running.iter() {
+            info!("取消刷新用户 {} 的数据", player_id);
+            cancel.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+// 刷新用户数据，cache 为 true 时优先读取缓存
+//
+// 需要从服务器获取时返回获取的用户 ID，与刷新进度中的用户 ID 一致；只读取了缓存时返回 None
+async fn update_data(
+    cache: bool,
+    mut player_id: String,
+    request_config: &RequestConfig,
+    fetch_lock: &tokio::sync::Mutex<()>,
+    cancel: &AtomicBool,
+    service_tx: &UiRepaintSender,
+) -> Option<String> {
+    let _ = service_tx.send(Event::Normal(t!("status.loading")));
+    if cache {
+        // 从缓存中获取数据
+        if let Ok(user_vec) = get_player_id_vec()
+            && !user_vec.is_empty()
+        {
+            if player_id.is_empty() {
+                player_id = user_vec[0].clone();
+                let _ = service_tx.send(Event::Player(user_vec));
+            }
+
+            // 第一次加载时尝试读缓存文件中的统计内容，加快首屏加载速度
+            match gacha_statistics_from_cache(player_id.clone()) {
+                Ok(gacha_statistics_data) => {
+                    let _ = service_tx
+                        .send(Event::Gacha((player_id, gacha_statistics_data)));
+                    let _ = service_tx.send(Event::Normal(t!("status.showing_cache")));
+                    info!("刷新统计图");
+                    return None;
                 }
-                NeedUpdate(update_info) => {
-                    self.update_info = Some(update_info);
+                Err(err) => {
+                    let _ = service_tx.send(Event::Warning(t!("status.no_cache")));
+                    info!("无缓存：{}", err);
                 }
-                _ => {
-                    warn!("接收到了错误的消息");
+            }
+        }
+
+        let _ = service_tx.send(Event::Warning(t!("status.first_use")));
+    }
+
+    // 从服务器获取抽卡数据，同时只允许一个获取任务写入用户数据
+    let Ok(_fetching) = fetch_lock.try_lock() else {
+        info!("已有数据正在刷新，忽略本次请求");
+        let _ = service_tx.send(Event::Warning(t!("status.refresh_running")));
+        return Some(player_id);
+    };
+    match gacha_statistics(player_id.clone(), request_config, cancel, service_tx).await {
+        Ok((player_id, gacha_statistics_data, failed_pools)) => {
+            let _ = service_tx.send(Event::Gacha((player_id, gacha_statistics_data)));
+            if failed_pools.is_empty() {
+                let _ = service_tx.send(Event::Normal(t!("status.fetch_done")));
+            } else {
+                // 部分卡池获取失败，其余卡池数据已保存
+                for (card_pool_type, err) in failed_pools.iter() {
+                    warn!("卡池 {} 获取失败：{}", card_pool_type, err);
                 }
+                let _ = service_tx.send(Event::PoolsFailed(failed_pools));
+            }
+            info!("刷新统计图");
+
+            // 刷新当前保存数据的用户列表
+            if let Ok(user_vec) = get_player_id_vec() {
+                let _ = service_tx.send(Event::Player(user_vec));
             }
         }
+        Err(CoreError::FetchCancelled) => {
+            let _ = service_tx.send(Event::Warning(t!("status.refresh_cancelled")));
+        }
+        Err(err) => {
+            error!("抽卡数据统计失败：{}", err);
+            let _ = service_tx.send(Event::Error(err));
+        }
+    }
+    Some(player_id)
+}
+
+impl eframe::App for MainView {
+    fn ui(&mut self, ui: &mut Ui, _frame: &mut Frame) {
+        // 一帧内处理完所有待处理的事件
+        while let Ok(event) = self.view_rx.try_recv() {
+            self.handle_event(event, ui.ctx());
+        }
+        self.window.track(ui.ctx());
+
+        if self.show_message_log {
+            egui::Panel::bottom("message_log")
+                .resizable(true)
+                .default_size(160.0)
+                .show_inside(ui, |ui| self.message_log_view(ui));
+        }
 
         CentralPanel::default().show_inside(ui, |ui| {
             ui.horizontal(|ui| {
                 // 切换显示模式
                 global_theme_switch(ui);
 
-                let update_button = ui.button("获取数据更新");
+                // 切换界面语言
+                let language = self.language;
+                ComboBox::from_id_salt("language")
+                    .selected_text(self.language.display_name())
+                    .width(80.0)
+                    .show_ui(ui, |ui| {
+                        for language in Language::ALL {
+                            ui.selectable_value(&mut self.language, language, language.display_name());
+                        }
+                    })
+                    .response
+                    .on_hover_text(t!("label.language"));
+                if self.language != language {
+                    self.switch_language(ui.ctx());
+                }
+
+                // 刷新中不允许重复刷新
+                let fetching = !self.fetch_progress.is_empty();
+                let update_button = ui.add_enabled(!fetching, egui::Button::new(t!("button.update_data")));
                 if update_button.clicked() {
                     info!("开始刷新数据...");
+                    let player_id = self.player_id_selected.clone();
+                    self.fetch_progress.insert(player_id.clone(), FetchProgress::new(player_id));
                     let _ = &self
                         .view_tx
-                        .send(UpdateData(false, self.player_id_selected.clone()));
+                        .send(Command::UpdateData(false, self.player_id_selected.clone()));
                     let _ = &self.gacha_statistic_view_vec.clear();
                 }
 
@@ -324,112 +822,219 @@ impl eframe::App for MainView {
                     self.player_id_last_selected = self.player_id_selected.clone();
                     let _ = &self
                         .view_tx
-                        .send(UpdateData(true, self.player_id_selected.clone()));
-                    let _ = self.gacha_statistic_view_vec.clear();
+                        .send(Command::UpdateData(true, self.player_id_selected.clone()));
+                    self.gacha_statistic_view_vec.clear();
                 }
 
-                ui.label("选择用户:");
+                ui.label(t!("label.select_user"));
                 ComboBox::from_id_salt("player_id")
-                    .selected_text(&self.player_id_selected)
+                    .selected_text(self.account_label(&self.player_id_selected))
                     .show_ui(ui, |ui| {
                         for player_id in self.player_id_vec.clone() {
-                            ui.selectable_value(
-                                &mut self.player_id_selected,
-                                player_id.clone(),
-                                player_id,
-                            );
+                            let label = self.account_label(&player_id);
+                            ui.selectable_value(&mut self.player_id_selected, player_id, label);
                         }
                     });
 
-                let add_user_button = ui.button("获取新用户");
+                let add_user_button = ui.add_enabled(!fetching, egui::Button::new(t!("button.add_user")));
                 if add_user_button.clicked() {
                     info!("开始获取新用户...");
-                    let _ = &self.view_tx.send(UpdateData(false, "".to_string()));
+                    self.fetch_progress.insert(String::new(), FetchProgress::new(String::new()));
+                    let _ = &self.view_tx.send(Command::UpdateData(false, "".to_string()));
                     let _ = &self.gacha_statistic_view_vec.clear();
                 }
 
+                let accounts_button = ui.button(t!("button.accounts"));
+                if accounts_button.clicked() {
+                    self.view = View::Accounts;
+                    self.pending_delete = None;
+                    let _ = self.view_tx.send(Command::LoadAccounts);
+                }
+
+                let aggregate_button = ui.button(t!("button.aggregate"));
+                if aggregate_button.clicked() {
+                    self.view = View::Aggregate;
+                    // 默认汇总全部用户
+                    if self.aggregate_selected.is_empty() {
+                        self.aggregate_selected = self.player_id_vec.iter().cloned().collect();
+                    }
+                    self.load_aggregate();
+                }
+
+                let compare_button = ui.button(t!("button.compare"));
+                if compare_button.clicked() {
+                    self.view = View::Compare;
+                    // 默认对比当前用户与另一个用户
+                    if self.compare_left.is_empty() {
+                        self.compare_left = self.player_id_selected.clone();
+                        self.compare_right = self
+                            .player_id_vec
+                            .iter()
+                            .find(|player_id| **player_id != self.player_id_selected)
+                            .cloned()
+                            .unwrap_or_default();
+                    }
+                    self.load_comparison();
+                }
+
+                let activity_button = ui.button(t!("button.activity"));
+                if activity_button.clicked() {
+                    self.view = View::Activity;
+                    self.activity_requested = None;
+                }
+
+                let roster_button = ui.button(t!("button.roster"));
+                if roster_button.clicked() {
+                    self.view = View::Roster;
+                    self.roster_requested = None;
+                }
+
+                ui.add_enabled_ui(!self.player_id_selected.is_empty(), |ui| {
+                    ui.menu_button(t!("button.export"), |ui| {
+                        ui.checkbox(&mut self.export_mask_uid, t!("label.mask_uid"));
+                        for (format, label) in [
+                            (ExportFormat::Png, t!("label.export_png")),
+                            (ExportFormat::Svg, t!("label.export_svg")),
+                            (ExportFormat::Html, t!("label.export_html")),
+                            (ExportFormat::Markdown, t!("label.export_markdown")),
+                        ] {
+                            if ui.button(label).clicked() {
+                                self.export_summary(format);
+                                ui.close();
+                            }
+                        }
+                        if ui.button(t!("label.copy_markdown")).clicked() {
+                            let options = self.export_options();
+                            let markdown = render_markdown(&self.player_id_selected, &self.gacha_statistics, options);
+                            ui.ctx().copy_text(markdown);
+                            self.set_message(Message::info(t!("status.markdown_copied")));
+                            ui.close();
+                        }
+                    });
+                });
+
+                let settings_button = ui.button(t!("button.settings"));
+                if settings_button.clicked() {
+                    self.view = View::Settings;
+                }
+
                 if let Some(update_info) = &self.update_info {
-                    let new_version = ui.button(format!("新版本 {}", update_info.tag_name));
+                    let new_version = ui.button(t!("button.new_version", version = update_info.tag_name));
                     if new_version.clicked() {
                         self.view = View::Update;
                     }
                 }
 
-                if self.message.success {
-                    ui.label(&self.message.message);
-                } else {
-                    ui.colored_label(Color32::from_rgb(232, 176, 4), &self.message.message);
+                ui.toggle_value(&mut self.show_message_log, t!("button.message_log"));
+
+                ui.colored_label(self.message.severity.color(ui), &self.message.message);
+                if !self.message.suggestion.is_empty() {
+                    ui.weak(&self.message.suggestion);
                 }
             });
 
+            for progress in self.fetch_progress.values() {
+                ui.horizontal(|ui| {
+                    let text = match progress.phase {
+                        FetchPhase::ReadingLog => t!("label.fetch_reading_log"),
+                        FetchPhase::Fetching => t!(
+                            "label.fetch_progress",
+                            pool = progress.pool,
+                            total = progress.pool_total,
+                            records = progress.records
+                        ),
+                        FetchPhase::Saving => t!("label.fetch_saving", records = progress.records),
+                    };
+                    ui.add(egui::ProgressBar::new(progress.fraction()).desired_width(400.0).text(text));
+                    if ui.button(t!("button.cancel_fetch")).clicked() {
+                        let _ = self.view_tx.send(Command::CancelFetch);
+                    }
+                });
+            }
+
             if let View::Home = self.view {
-                // 刷新统计图内容
-                let _ = &self.create_bar_chart(&self.gacha_statistics.clone());
+                // 统计图只在数据或选择变化后重新生成
+                self.create_pool_charts();
                 let gacha_statistic_view_vec = &mut self.gacha_statistic_view_vec;
+                let pool_selection = &mut self.pool_selection;
+                let icon_cache = &mut self.icon_cache;
+                let show_icons = self.show_icons;
+                let time_zone = self.time_zone;
+                let mut open_detail = None;
+                let mut selection_changed = false;
 
                 egui::ScrollArea::vertical()
                     .scroll_source(ScrollSource::MOUSE_WHEEL)
                     .show(ui, |ui| {
-                        for _ in 0..(gacha_statistic_view_vec.len() as f32 / 3.0).ceil() as i32 {
+                        // 每行的卡片数量随窗口宽度变化
+                        let columns = card_columns(ui.available_width(), ui.spacing().item_spacing.x);
+                        for row in gacha_statistic_view_vec.chunks_mut(columns) {
                             ui.vertical(|ui| {
                                 ui.horizontal(|ui| {
                                     ui.group(|ui| {
-                                        for _ in 0..min(3, gacha_statistic_view_vec.len() as i32) {
-                                            let mut item = gacha_statistic_view_vec.remove(0);
+                                        for item in row.iter_mut() {
                                             ui.vertical(|ui| {
-                                                match item.card_pool_type {
-                                                    1 => {
-                                                        ui.label("角色活动唤取");
-                                                    }
-                                                    2 => {
-                                                        ui.label("武器活动唤取");
-                                                    }
-                                                    3 => {
-                                                        ui.label("角色常驻唤取");
-                                                    }
-                                                    4 => {
-                                                        ui.label("武器常驻唤取");
-                                                    }
-                                                    5 => {
-                                                        ui.label("新手唤取");
-                                                    }
-                                                    6 => {
-                                                        ui.label("新手自选唤取");
-                                                    }
-                                                    7 => {
-                                                        ui.label("新手自选唤取（感恩定向唤取）");
-                                                    }
-                                                    8 => {
-                                                        ui.label("角色新旅唤取");
-                                                    }
-                                                    9 => {
-                                                        ui.label("武器新旅唤取");
-                                                    }
-                                                    10 => {
-                                                        ui.label("角色联动唤取");
-                                                    }
-                                                    11 => {
-                                                        ui.label("武器联动唤取");
-                                                    }
-                                                    _ => {
-                                                        ui.label("新卡池");
+                                                let title = ui.link(card_pool_name(item.card_pool_type));
+                                                if title.on_hover_text(t!("label.pool_detail_hint")).clicked() {
+                                                    open_detail = Some(item.card_pool_type);
+                                                }
+                                                if let Some(index) = item.pie_chart.show(ui) {
+                                                    // 再次选择同一星级时取消选择
+                                                    if pool_selection.get(&item.card_pool_type) == Some(&index) {
+                                                        pool_selection.remove(&item.card_pool_type);
+                                                    } else {
+                                                        pool_selection.insert(item.card_pool_type, index);
                                                     }
+                                                    selection_changed = true;
                                                 }
-                                                item.pie_chart.show(ui);
+                                                egui::CollapsingHeader::new(t!("label.pity_charts"))
+                                                    .id_salt(("pity_charts", item.card_pool_type))
+                                                    .show(ui, |ui| {
+                                                        item.pity_histogram.show(ui);
+                                                        item.pity_timeline.show(ui);
+                                                    });
 
-                                                ui.label(format!(
-                                                    "当前累计[{}]抽，已垫[{}]抽，5星[{}]个",
-                                                    item.total,
-                                                    item.pull_count,
-                                                    item.detail.len()
+                                                ui.label(t!(
+                                                    "label.pool_summary",
+                                                    total = item.total,
+                                                    pull = item.pull_count,
+                                                    five = item.detail.len()
                                                 ));
+                                                // 按统计图中选中的星级列出记录，默认列出五星
+                                                let records: &[GachaStatisticsDataItem] = match pool_selection.get(&item.card_pool_type) {
+                                                    Some(&FOUR_STAR_INDEX) => &item.four_star_detail,
+                                                    Some(&THREE_STAR_INDEX) => {
+                                                        ui.weak(t!("label.three_star_not_listed"));
+                                                        &[]
+                                                    }
+                                                    _ => &item.detail,
+                                                };
                                                 ui.horizontal_wrapped(|ui| {
-                                                    ui.set_max_width(285.0);
-                                                    for item in item.detail {
-                                                        ui.label(format!(
-                                                            "{}[{}]",
-                                                            item.name, item.count
-                                                        ));
+                                                    ui.set_max_width(CARD_WIDTH);
+                                                    for item in records {
+                                                        // 名称按界面语言展示，与抽卡记录的语言无关
+                                                        let name = resource_name(
+                                                            item.resource_id,
+                                                            i18n::language(),
+                                                            &item.name,
+                                                        );
+                                                        if show_icons
+                                                            && let Some(texture) = icon_cache.get(ui.ctx(), item.resource_id)
+                                                        {
+                                                            ui.add(egui::Image::new(&texture).fit_to_exact_size(egui::vec2(LIST_ICON_SIZE, LIST_ICON_SIZE)));
+                                                        }
+                                                        let label = ui.label(format!("{}[{}]", name, item.count));
+                                                        let hover_text = [
+                                                            Some(t!(
+                                                                "label.obtained_at",
+                                                                time = time_zone.format(&item.time)
+                                                            )),
+                                                            resource_description(item.resource_id),
+                                                        ]
+                                                        .into_iter()
+                                                        .flatten()
+                                                        .collect::<Vec<_>>();
+                                                        label.on_hover_text(hover_text.join("\n"));
                                                     }
                                                 });
                                             });
@@ -439,15 +1044,24 @@ impl eframe::App for MainView {
                             });
                         }
                     });
+
+                if selection_changed {
+                    self.gacha_statistic_view_vec.clear();
+                }
+                if let Some(card_pool_type) = open_detail {
+                    self.view = View::PoolDetail(card_pool_type);
+                }
             }
 
             if let View::Update = self.view {
+                let mut skip_version = None;
+                let mut message = None;
                 if let Some(update_info) = &self.update_info {
                     ui.vertical_centered_justified(|ui| {
                         ui.group(|ui| {
-                            ui.label(format!("发现新版本：{}", update_info.tag_name));
+                            ui.label(t!("label.new_version_found", version = update_info.tag_name));
                             ui.label("");
-                            ui.label("更新日志：");
+                            ui.label(t!("label.changelog"));
                             CommonMarkViewer::new().show(
                                 ui,
                                 &mut self.markdown_cache,
@@ -455,26 +1069,67 @@ impl eframe::App for MainView {
                             );
                             ui.label("");
 
-                            let download_button = ui.button("下载更新");
+                            if let Some(progress) = &self.download_progress {
+                                ui.add(
+                                    egui::ProgressBar::new(progress.fraction())
+                                        .show_percentage()
+                                        .text(t!(
+                                            "label.download_progress",
+                                            downloaded = format_bytes(progress.downloaded),
+                                            total = format_bytes(progress.total),
+                                            speed = format_bytes(progress.bytes_per_second as u64)
+                                        )),
+                                );
+                                if ui.button(t!("button.cancel_download")).clicked() {
+                                    let _ = self.view_tx.send(Command::CancelDownload);
+                                }
+                            }
+
+                            if let Some(package) = &self.downloaded_update {
+                                let install_button = ui.add_enabled(
+                                    !self.installing,
+                                    egui::Button::new(t!("button.install_update")),
+                                );
+                                if install_button.clicked() {
+                                    self.installing = true;
+                                    let _ = self
+                                        .view_tx
+                                        .send(Command::InstallUpdate(package.clone(), update_info.tag_name.clone()));
+                                }
+                            }
+
+                            // 未发布签名清单的版本无法校验，只能手动下载
+                            let verifiable = update_info.verifiable();
+                            if !verifiable {
+                                ui.colored_label(Severity::Warning.color(ui), t!("label.update_unverifiable"));
+                            }
+                            ui.hyperlink_to(t!("button.open_release_page"), update_info.page_url());
+
+                            let download_button = ui.add_enabled(
+                                verifiable && self.download_progress.is_none(),
+                                egui::Button::new(t!("button.download_update")),
+                            );
                             if download_button.clicked() {
                                 self.view = View::Update;
                                 if let Some(path) = rfd::FileDialog::new()
-                                    .set_title("请选择更新包存放位置")
+                                    .set_title(t!("label.pick_update_folder"))
                                     .pick_folder()
                                 {
                                     let picked_path = path.display().to_string();
                                     info!("选择的文件 {:?}", picked_path);
+                                    self.download_progress = Some(DownloadProgress::default());
                                     let _ = &self
                                         .view_tx
-                                        .send(DownloadFile(update_info.clone(), picked_path));
+                                        .send(Command::DownloadFile(Box::new(update_info.clone()), picked_path));
                                 } else {
-                                    self.message = Message {
-                                        success: true,
-                                        message: "用户取消升级...".to_string(),
-                                    }
+                                    message = Some(Message::info(t!("status.update_cancelled")));
                                 }
                             }
-                            let cancel_button = ui.button("取消更新");
+                            let skip_button = ui.button(t!("button.skip_version"));
+                            if skip_button.clicked() {
+                                skip_version = Some(update_info.tag_name.clone());
+                            }
+                            let cancel_button = ui.button(t!("button.cancel_update"));
                             if cancel_button.clicked() {
                                 self.view = View::Home;
                             }
@@ -483,70 +1138,1211 @@ impl eframe::App for MainView {
                 } else {
                     self.view = View::Home;
                 }
+
+                if let Some(message) = message {
+                    self.set_message(message);
+                }
+
+                if let Some(skip_version) = skip_version {
+                    info!("跳过版本 {}", skip_version);
+                    self.update_config.skipped_version = skip_version;
+                    self.update_info = None;
+                    self.view = View::Home;
+                }
+            }
+
+            if let View::Settings = self.view {
+                self.settings_view(ui);
+            }
+
+            if let View::Accounts = self.view {
+                self.accounts_view(ui);
+            }
+
+            if let View::Aggregate = self.view {
+                self.aggregate_view(ui);
+            }
+
+            if let View::Compare = self.view {
+                self.compare_view(ui);
+            }
+
+            if let View::Activity = self.view {
+                self.activity_view(ui);
+            }
+
+            if let View::PoolDetail(card_pool_type) = self.view {
+                self.pool_detail_view(ui, card_pool_type);
+            }
+
+            if let View::Roster = self.view {
+                self.roster_view(ui);
             }
         });
     }
 
     fn on_exit(&mut self) {
         info!("应用退出...");
-        self.on_exit.swap(true, Ordering::Relaxed);
-        info!("储存用户配置...");
-        let _ = fs::create_dir_all("./data");
-        if let Ok(config_str) = toml::to_string(&Config {
-            dark_mode: self.dark_mode,
-            player_id_selected: self.player_id_selected.clone(),
-        }) {
-            if let Ok(mut file) = OpenOptions::new()
-                .write(true)
-                .truncate(true)
-                .create(true)
-                .open("./data/config.toml")
-            {
-                let _ = file.write_all(config_str.as_bytes());
-            }
+        // 通知后台服务取消进行中的任务，并等待其结束
+        let (ack_tx, ack_rx) = mpsc::channel();
+        if self.view_tx.send(Command::Shutdown(ack_tx)).is_ok() {
+            let _ = ack_rx.recv_timeout(SHUTDOWN_TIMEOUT + Duration::from_secs(1));
         }
+        info!("储存用户配置...");
+        self.config().save();
+    }
+}
+
+// 卡池卡片的宽度及统计图高度
+const CARD_WIDTH: f32 = 285.0;
+const CHART_HEIGHT: f32 = 150.0;
+
+// 分组框的内边距及边框
+const CARD_GROUP_MARGIN: f32 = 7.0;
+
+// 按可用宽度计算每行可放下的卡池卡片数量，至少一张
+fn card_columns(available_width: f32, spacing: f32) -> usize {
+    (((available_width - CARD_GROUP_MARGIN * 2.0 + spacing) / (CARD_WIDTH + spacing)).floor() as usize).max(1)
+}
+
+// 卡池统计图中各星级的数据下标
+const THREE_STAR_INDEX: usize = 0;
+const FOUR_STAR_INDEX: usize = 1;
+const FIVE_STAR_INDEX: usize = 2;
+
+// 各星级在统计图及记录列表中的颜色
+const THREE_STAR_COLOR: Color32 = Color32::from_rgb(99, 176, 225);
+const FOUR_STAR_COLOR: Color32 = Color32::from_rgb(171, 101, 207);
+const FIVE_STAR_COLOR: Color32 = Color32::from_rgb(225, 216, 115);
+
+// 按星级选择名称颜色，3星使用默认文字颜色
+fn star_color(quality_level: i32, ui: &Ui) -> Color32 {
+    match quality_level {
+        5 => FIVE_STAR_COLOR,
+        4 => FOUR_STAR_COLOR,
+        _ => ui.visuals().text_color(),
     }
 }
 
+// 角色及武器列表及首页五星列表中图标的尺寸
+const ROSTER_ICON_SIZE: f32 = 32.0;
+const LIST_ICON_SIZE: f32 = 20.0;
+
+// 资源的属性、武器类型及登场版本，未收录的资源为空
+fn resource_description(resource_id: i32) -> Option<String> {
+    let info = resource_info(resource_id)?;
+    let parts: Vec<String> = [
+        info.element.map(|element| t!(&format!("element.{}", element.key()))),
+        info.weapon_type.map(|weapon_type| t!(&format!("weapon_type.{}", weapon_type.key()))),
+        info.release.as_ref().map(|release| t!("label.release_version", version = release)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    (!parts.is_empty()).then(|| parts.join(" · "))
+}
+
+// 卡池详情页中统计图的尺寸
+const DETAIL_PIE_SIZE: [f32; 2] = [360.0, 240.0];
+const DETAIL_CHART_SIZE: [f32; 2] = [440.0, 220.0];
+
 struct GachaStatisticsView {
     card_pool_type: i32,
     total: i32,
     pull_count: i32,
     pie_chart: PieChart,
+    four_star_detail: Vec<GachaStatisticsDataItem>,
+    pity_histogram: PityHistogram,
+    pity_timeline: PityTimeline,
     detail: Vec<GachaStatisticsDataItem>,
 }
 
-impl MainView {
-    fn create_bar_chart(&mut self, gacha_statistic: &GachaStatistics) {
-        if self.gacha_statistic_view_vec.is_empty() {
-            let mut gacha_statistic_view_vec = vec![];
-            for (card_pool_type, gacha_statistics_data) in gacha_statistic.iter() {
-                let pie_chart = PieChart::new(
-                    gacha_statistics_data.card_pool_type.to_string(),
-                    vec![
-                        (
-                            gacha_statistics_data.three_count as f64,
-                            "3星".to_string(),
-                            Color32::from_rgb(99, 176, 225),
-                        ),
-                        (
-                            gacha_statistics_data.four_count as f64,
-                            "4星".to_string(),
-                            Color32::from_rgb(171, 101, 207),
-                        ),
-                        (
-                            gacha_statistics_data.five_count as f64,
-                            "5星".to_string(),
-                            Color32::from_rgb(225, 216, 115),
-                        ),
-                    ],
-                );
+// 用户选择框中展示的名称，设置了昵称时附带 UID
+fn account_label(accounts: &[AccountInfo], player_id: &str) -> String {
+    accounts
+        .iter()
+        .find(|account| account.player_id == player_id && !account.nickname.is_empty())
+        .map(|account| format!("{} ({})", account.nickname, player_id))
+        .unwrap_or_else(|| player_id.to_string())
+}
+
+// 对比表格，先列出各项统计及差值，再按出金抽数对齐列出两个用户的五星
+fn comparison_grid(ui: &mut Ui, card_pool_type: i32, left_label: &str, right_label: &str, pool: &PoolComparison) {
+    let (left, right) = (&pool.left, &pool.right);
+    egui::Grid::new(("comparison", card_pool_type)).num_columns(4).striped(true).spacing([20.0, 6.0]).show(ui, |ui| {
+        ui.label("");
+        ui.strong(left_label);
+        ui.strong(right_label);
+        ui.strong(t!("label.compare_difference"));
+        ui.end_row();
+
+        let rows = [
+            (t!("label.aggregate_total"), left.total, right.total),
+            (t!("label.five_star"), left.five_count, right.five_count),
+            // 对比的是单个卡池，一方没有数据时未出金抽数为 0
+            (t!("label.aggregate_current_pity"), left.pull_count.unwrap_or(0), right.pull_count.unwrap_or(0)),
+        ];
+        for (name, left, right) in rows {
+            ui.label(name);
+            ui.label(left.to_string());
+            ui.label(right.to_string());
+            ui.label(format!("{:+}", right - left));
+            ui.end_row();
+        }
+
+        let format_pity = |pity: Option<f64>| pity.map(|pity| format!("{:.1}", pity)).unwrap_or_else(|| "-".to_string());
+        ui.label(t!("label.aggregate_average_pity"));
+        ui.label(format_pity(left.average_pity()));
+        ui.label(format_pity(right.average_pity()));
+        ui.label(match (left.average_pity(), right.average_pity()) {
+            (Some(left), Some(right)) => format!("{:+.1}", right - left),
+            _ => "-".to_string(),
+        });
+        ui.end_row();
+
+        let five_star_label = |item: &Option<GachaStatisticsDataItem>| {
+            item.as_ref()
+                .map(|item| format!("{}[{}]", resource_name(item.resource_id, i18n::language(), &item.name), item.count))
+                .unwrap_or_default()
+        };
+        for (index, (left, right)) in pool.five_stars.iter().enumerate() {
+            ui.label(if index == 0 { t!("label.compare_five_stars") } else { String::new() });
+            ui.label(five_star_label(left));
+            ui.label(five_star_label(right));
+            ui.label("");
+            ui.end_row();
+        }
+    });
+}
+
+// 日历中一天的说明：各卡池抽数及获得的五星
+fn activity_detail(period: &PeriodActivity) -> String {
+    let mut lines: Vec<_> = period
+        .pulls
+        .iter()
+        .map(|(card_pool_type, pulls)| format!("{}: {}", card_pool_name(*card_pool_type), pulls))
+        .collect();
+    for (_, item) in period.five_stars.iter() {
+        lines.push(format!("★ {}", resource_name(item.resource_id, i18n::language(), &item.name)));
+    }
+    lines.join("\n")
+}
+
+// 卡池在统计图中的颜色
+fn pool_color(card_pool_type: i32) -> Color32 {
+    const PALETTE: [Color32; 7] = [
+        Color32::from_rgb(225, 216, 115),
+        Color32::from_rgb(171, 101, 207),
+        Color32::from_rgb(99, 176, 225),
+        Color32::from_rgb(230, 126, 90),
+        Color32::from_rgb(96, 190, 140),
+        Color32::from_rgb(200, 120, 160),
+        Color32::from_rgb(150, 150, 150),
+    ];
+    PALETTE[(card_pool_type.max(1) - 1) as usize % PALETTE.len()]
+}
+
+// 汇总表格中一行的统计数据
+fn summary_row(ui: &mut Ui, summary: &StatisticsSummary) {
+    ui.label(summary.total.to_string());
+    ui.label(summary.five_count.to_string());
+    ui.label(summary.average_pity().map(|pity| format!("{:.1}", pity)).unwrap_or_else(|| "-".to_string()));
+    ui.label(summary.pull_count.map(|pull_count| pull_count.to_string()).unwrap_or_else(|| "-".to_string()));
+    ui.end_row();
+}
+
+// 字节数转换为便于阅读的大小
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+// 更新来源名称
+fn update_source_name(source: &UpdateSource) -> String {
+    match source {
+        UpdateSource::GitHub => "GitHub".to_string(),
+        UpdateSource::Mirror(_) => t!("label.update_source_mirror"),
+        UpdateSource::Custom(_) => t!("label.update_source_custom"),
+    }
+}
+
+// 区服名称
+fn region_name(region: Region) -> String {
+    t!(&format!("region.{}", region.key()))
+}
+
+fn time_zone_name(time_zone: DisplayTimeZone) -> String {
+    match time_zone {
+        DisplayTimeZone::Local => t!("label.local_time"),
+        DisplayTimeZone::Utc => t!("label.utc_time"),
+        DisplayTimeZone::Server(region) => t!("label.server_time", region = region_name(region)),
+    }
+}
+
+impl MainView {
+    // 处理后台服务发送的事件
+    fn handle_event(&mut self, event: Event, ctx: &egui::Context) {
+        match event {
+            Event::Normal(message) => {
+                self.set_message(Message::info(message));
+            }
+            Event::Warning(message) => {
+                self.set_message(Message::warning(message));
+            }
+            Event::Error(err) => {
+                self.set_message(Message::error(&err));
+            }
+            Event::PoolsFailed(failed_pools) => {
+                let card_pool_names = failed_pools
+                    .iter()
+                    .map(|(card_pool_type, _)| card_pool_name(*card_pool_type))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                if let Some((_, err)) = failed_pools.first() {
+                    let mut message = Message::error(err);
+                    message.message = t!(
+                        "status.pools_failed",
+                        pools = card_pool_names,
+                        reason = message.message
+                    );
+                    self.set_message(message);
+                }
+            }
+            Event::Gacha((player_id, gacha_statistic)) => {
+                self.player_id_selected = player_id.clone();
+                self.player_id_last_selected = player_id;
+                self.gacha_statistics = gacha_statistic;
+                self.gacha_statistic_view_vec.clear();
+            }
+            Event::Player(player_id_vec) => {
+                if !player_id_vec.is_empty() {
+                    for player_id in player_id_vec.clone() {
+                        if !self.player_id_vec.contains(&player_id) {
+                            self.player_id_selected = player_id.clone();
+                            self.player_id_last_selected = player_id;
+                        }
+                    }
+                }
+
+                // 当前用户已被隐藏或删除时切换到其他用户
+                if !self.player_id_selected.is_empty() && !player_id_vec.contains(&self.player_id_selected) {
+                    self.player_id_selected = player_id_vec.first().cloned().unwrap_or_default();
+                    self.gacha_statistics = GachaStatistics::new();
+                    self.gacha_statistic_view_vec.clear();
+                }
+
+                self.player_id_vec = player_id_vec;
+            }
+            Event::Accounts(accounts) => {
+                self.accounts = accounts;
+            }
+            Event::Aggregate(aggregate) => {
+                self.aggregate = aggregate;
+            }
+            Event::Activity(activity) => {
+                if self
+                    .activity_requested
+                    .as_ref()
+                    .is_some_and(|(player_id, _)| *player_id == activity.player_id)
+                {
+                    self.activity = Some(activity);
+                }
+            }
+            Event::Roster(roster) => {
+                if self.roster_requested.as_ref() == Some(&roster.player_id) {
+                    self.roster = Some(roster);
+                }
+            }
+            Event::Comparison(comparison) => {
+                // 忽略选择变化前发出的请求结果
+                if comparison.left_id == self.compare_left && comparison.right_id == self.compare_right {
+                    self.comparison = Some(comparison);
+                }
+            }
+            Event::NeedUpdate(update_info) => {
+                self.update_info = Some(*update_info);
+            }
+            Event::FetchProgressed(progress) => {
+                self.fetch_progress.insert(progress.player_id.clone(), progress);
+            }
+            Event::FetchFinished(player_id) => {
+                // 只结束该用户的刷新，其他用户的刷新仍在进行
+                self.fetch_progress.remove(&player_id);
+                // 刷新后记录数及更新时间有变化
+                match self.view {
+                    View::Accounts => {
+                        let _ = self.view_tx.send(Command::LoadAccounts);
+                    }
+                    View::Aggregate => self.load_aggregate(),
+                    View::Compare => self.load_comparison(),
+                    View::Activity => self.activity_requested = None,
+                    View::Roster => self.roster_requested = None,
+                    _ => {}
+                }
+            }
+            Event::DownloadProgressed(progress) => {
+                // 下载进度变化频繁，不记入消息历史
+                self.message = Message::info(t!(
+                    "status.downloading",
+                    percent = format!("{:.2}", progress.fraction() * 100.0),
+                    speed = format_bytes(progress.bytes_per_second as u64)
+                ));
+                self.download_progress = Some(progress);
+            }
+            Event::DownloadFinished(result) => {
+                self.download_progress = None;
+                let message = match result {
+                    Ok(path) => {
+                        let message = Message::info(t!("status.download_done", path = path.display()));
+                        self.downloaded_update = Some(path);
+                        message
+                    }
+                    Err(err) => Message::error(&err),
+                };
+                self.set_message(message);
+            }
+            Event::UpdateInstalled(result) => {
+                self.installing = false;
+                match result {
+                    Ok(exe) => {
+                        info!("更新安装完成，重新启动：{}", exe.display());
+                        // 新程序启动时会读取配置，需先保存
+                        self.config().save();
+                        match relaunch(&exe) {
+                            Ok(_) => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+                            Err(err) => self.set_message(Message::error(&err)),
+                        }
+                    }
+                    Err(err) => {
+                        self.set_message(Message::error(&err));
+                    }
+                }
+            }
+        }
+    }
+
+    // 更新当前消息并记入历史
+    fn set_message(&mut self, message: Message) {
+        if !message.message.is_empty() {
+            if self.message_log.len() >= MESSAGE_LOG_LIMIT {
+                self.message_log.pop_front();
+            }
+            self.message_log.push_back(message.clone());
+        }
+        self.message = message;
+    }
+
+    fn message_log_view(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.strong(t!("label.message_log"));
+            if ui.small_button(t!("button.clear_log")).clicked() {
+                self.message_log.clear();
+            }
+        });
+        ui.separator();
+
+        egui::ScrollArea::vertical()
+            .auto_shrink(false)
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for message in self.message_log.iter() {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.weak(message.time.format("%H:%M:%S").to_string());
+                        ui.colored_label(message.severity.color(ui), format!("[{}]", message.severity.name()));
+                        ui.label(&message.message);
+                        if !message.suggestion.is_empty() {
+                            ui.weak(&message.suggestion);
+                        }
+                    });
+                }
+            });
+    }
+
+    fn config(&self) -> Config {
+        Config {
+            dark_mode: self.dark_mode,
+            player_id_selected: self.player_id_selected.clone(),
+            request: self.request_config.clone(),
+            language: self.language,
+            time_zone: self.time_zone,
+            update: self.update_config.clone(),
+            network: self.network_config.clone(),
+            donut_chart: self.donut_chart,
+            window: self.window,
+            show_icons: self.show_icons,
+        }
+    }
+
+    // 切换界面语言，重新生成带文字的统计图
+    fn switch_language(&mut self, ctx: &egui::Context) {
+        info!("切换界面语言：{:?}", self.language);
+        i18n::set_language(self.language);
+        setup_fonts(ctx, self.language);
+        ctx.send_viewport_cmd(egui::ViewportCommand::Title(t!("app.title", version = VERSION)));
+        self.gacha_statistic_view_vec.clear();
+    }
+
+    fn settings_view(&mut self, ui: &mut Ui) {
+        ui.vertical_centered_justified(|ui| {
+            ui.group(|ui| {
+                egui::Grid::new("settings").num_columns(2).spacing([20.0, 10.0]).show(ui, |ui| {
+                    ui.label(t!("label.time_zone"));
+                    let time_zone = self.time_zone;
+                    ComboBox::from_id_salt("time_zone")
+                        .selected_text(time_zone_name(self.time_zone))
+                        .show_ui(ui, |ui| {
+                            let time_zones = [DisplayTimeZone::Local, DisplayTimeZone::Utc]
+                                .into_iter()
+                                .chain(Region::ALL.into_iter().map(DisplayTimeZone::Server));
+                            for time_zone in time_zones {
+                                ui.selectable_value(&mut self.time_zone, time_zone, time_zone_name(time_zone));
+                            }
+                        });
+                    // 出金时间线按时区展示，需重新生成
+                    if self.time_zone != time_zone {
+                        self.gacha_statistic_view_vec.clear();
+                    }
+                    ui.end_row();
+
+                    // 从服务器获取数据时使用的语言
+                    let language_code = self.request_config.language_code.clone();
+                    ui.label(t!("label.data_language")).on_hover_text(t!("label.data_language_hint"));
+                    ComboBox::from_id_salt("data_language")
+                        .selected_text(
+                            Language::ALL
+                                .into_iter()
+                                .find(|language| language.code() == language_code)
+                                .map(|language| language.display_name().to_string())
+                                .unwrap_or_else(|| t!("label.follow_url")),
+                        )
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.request_config.language_code, String::new(), t!("label.follow_url"));
+                            for language in Language::ALL {
+                                ui.selectable_value(
+                                    &mut self.request_config.language_code,
+                                    language.code().to_string(),
+                                    language.display_name(),
+                                );
+                            }
+                        });
+                    if self.request_config.language_code != language_code {
+                        let _ = self.view_tx.send(Command::UpdateRequestConfig(self.request_config.clone()));
+                    }
+                    ui.end_row();
+
+                    ui.label("");
+                    if ui.checkbox(&mut self.donut_chart, t!("label.donut_chart")).changed() {
+                        self.gacha_statistic_view_vec.clear();
+                    }
+                    ui.end_row();
+
+                    ui.label("");
+                    let show_icons = ui.checkbox(&mut self.show_icons, t!("label.show_icons"));
+                    if show_icons.on_hover_text(t!("label.show_icons_hint", dir = ICON_DIR)).changed() {
+                        self.icon_cache.clear();
+                    }
+                    ui.end_row();
+
+                    // 也可使用 Ctrl +/- 调整，此处显示当前的缩放比例
+                    ui.label(t!("label.ui_scale"));
+                    ComboBox::from_id_salt("ui_scale")
+                        .selected_text(format!("{:.0}%", self.window.ui_scale * 100.0))
+                        .show_ui(ui, |ui| {
+                            for ui_scale in WindowConfig::UI_SCALES {
+                                let selected = (self.window.ui_scale - ui_scale).abs() < 0.01;
+                                if ui.selectable_label(selected, format!("{:.0}%", ui_scale * 100.0)).clicked() {
+                                    ui.ctx().set_zoom_factor(ui_scale);
+                                }
+                            }
+                        });
+                    ui.end_row();
+
+                    let mut beta = self.update_config.channel == UpdateChannel::Beta;
+                    ui.label("");
+                    if ui.checkbox(&mut beta, t!("label.beta_channel")).changed() {
+                        self.update_config.channel = if beta { UpdateChannel::Beta } else { UpdateChannel::Stable };
+                        // 切换更新通道后重新检查更新
+                        self.update_info = None;
+                        let _ = self.view_tx.send(Command::CheckUpdate(self.update_config.clone()));
+                    }
+                    ui.end_row();
+                });
+
+                ui.separator();
+                ui.heading(t!("label.network_settings"));
+                self.network_settings_view(ui);
+
+                ui.label("");
+                if ui.button(t!("button.back")).clicked() {
+                    self.view = View::Home;
+                }
+            });
+        });
+    }
+
+    fn pool_detail_view(&mut self, ui: &mut Ui, card_pool_type: i32) {
+        let time_zone = self.time_zone;
+
+        ui.vertical_centered_justified(|ui| {
+            ui.group(|ui| {
+                ui.heading(card_pool_name(card_pool_type));
+
+                // 切换用户后该卡池可能没有数据
+                let Some(data) = self.gacha_statistics.get(&card_pool_type).cloned() else {
+                    ui.label(t!("label.no_activity"));
+                    ui.label("");
+                    if ui.button(t!("button.back")).clicked() {
+                        self.view = View::Home;
+                    }
+                    return;
+                };
+                let run = PoolRunStatistics::from_data(&data);
+                let format_pity = |pity: Option<f64>| pity.map(|pity| format!("{:.1}", pity)).unwrap_or_else(|| "-".to_string());
+                let format_count = |count: Option<i32>| count.map(|count| count.to_string()).unwrap_or_else(|| "-".to_string());
+
+                ui.label(t!("label.pool_summary", total = data.total, pull = data.pull_count, five = data.five_count));
+                ui.horizontal(|ui| {
+                    let mut pie_chart = star_pie_chart(&data)
+                        .size(DETAIL_PIE_SIZE[0], DETAIL_PIE_SIZE[1])
+                        .donut(self.donut_chart)
+                        .selected(self.pool_selection.get(&card_pool_type).copied());
+                    if let Some(index) = pie_chart.show(ui) {
+                        // 与首页卡片共用选中状态
+                        if self.pool_selection.get(&card_pool_type) == Some(&index) {
+                            self.pool_selection.remove(&card_pool_type);
+                        } else {
+                            self.pool_selection.insert(card_pool_type, index);
+                        }
+                        self.gacha_statistic_view_vec.clear();
+                    }
+
+                    egui::Grid::new(("pool_run", card_pool_type)).num_columns(2).striped(true).spacing([20.0, 6.0]).show(ui, |ui| {
+                        let rows = [
+                            (t!("label.run_five_pity"), run.five_pity.to_string()),
+                            (t!("label.run_four_pity"), run.four_pity.to_string()),
+                            (t!("label.aggregate_average_pity"), format_pity(run.average_five_pity)),
+                            (t!("label.run_min_pity"), format_count(run.min_five_pity)),
+                            (t!("label.run_max_pity"), format_count(run.max_five_pity)),
+                            (t!("label.run_four_average_pity"), format_pity(run.average_four_pity)),
+                        ];
+                        for (label, value) in rows {
+                            ui.label(label);
+                            ui.label(value);
+                            ui.end_row();
+                        }
+                    });
+                });
+
+                ui.horizontal(|ui| {
+                    pity_histogram_chart(&data).size(DETAIL_CHART_SIZE[0], DETAIL_CHART_SIZE[1]).show(ui);
+                    pity_timeline_chart(&data, time_zone).size(DETAIL_CHART_SIZE[0], DETAIL_CHART_SIZE[1]).show(ui);
+                });
+
+                // 按统计图中选中的星级筛选，默认列出全部四星及五星，最新的排在最前
+                let selection = self.pool_selection.get(&card_pool_type).copied();
+                let mut records: Vec<(i32, GachaStatisticsDataItem)> = vec![];
+                if selection != Some(FOUR_STAR_INDEX) {
+                    records.extend(data.detail.into_iter().map(|item| (5, item)));
+                }
+                if selection != Some(FIVE_STAR_INDEX) {
+                    records.extend(data.four_star_detail.into_iter().map(|item| (4, item)));
+                }
+                records.sort_by_key(|(_, item)| std::cmp::Reverse(item.time));
+
+                ui.separator();
+                if selection == Some(THREE_STAR_INDEX) {
+                    ui.weak(t!("label.three_star_not_listed"));
+                }
+                egui::ScrollArea::vertical().max_height(ui.available_height() - 40.0).show(ui, |ui| {
+                    egui::Grid::new(("pool_records", card_pool_type)).num_columns(4).striped(true).spacing([20.0, 6.0]).show(ui, |ui| {
+                        ui.strong(t!("export.time"));
+                        ui.strong(t!("export.name"));
+                        ui.strong(t!("export.quality"));
+                        ui.strong(t!("export.pity"));
+                        ui.end_row();
+                        for (quality, item) in records {
+                            let color = if quality == 5 { FIVE_STAR_COLOR } else { FOUR_STAR_COLOR };
+                            ui.label(time_zone.format(&item.time));
+                            ui.colored_label(color, resource_name(item.resource_id, i18n::language(), &item.name));
+                            ui.colored_label(color, if quality == 5 { t!("label.five_star") } else { t!("label.four_star") });
+                            ui.label(item.count.to_string());
+                            ui.end_row();
+                        }
+                    });
+                });
+
+                ui.label("");
+                if ui.button(t!("button.back")).clicked() {
+                    self.view = View::Home;
+                }
+            });
+        });
+    }
+
+    fn account_label(&self, player_id: &str) -> String {
+        account_label(&self.accounts, player_id)
+    }
+
+    fn accounts_view(&mut self, ui: &mut Ui) {
+        let mut actions = vec![];
+        let time_zone = self.time_zone;
+
+        ui.vertical_centered_justified(|ui| {
+            ui.group(|ui| {
+                if self.accounts.is_empty() {
+                    ui.label(t!("label.no_accounts"));
+                }
+
+                egui::ScrollArea::both().max_height(ui.available_height() - 40.0).show(ui, |ui| {
+                    egui::Grid::new("accounts").num_columns(8).striped(true).spacing([12.0, 8.0]).show(ui, |ui| {
+                        ui.strong(t!("label.account_uid"));
+                        ui.strong(t!("label.account_nickname"));
+                        ui.strong(t!("label.account_region"));
+                        ui.strong(t!("label.account_last_fetch"));
+                        ui.strong(t!("label.account_records"));
+                        ui.strong(t!("label.account_hidden"));
+                        ui.strong(t!("label.account_url"));
+                        ui.label("");
+                        ui.end_row();
+
+                        for account in self.accounts.iter_mut() {
+                            let player_id = account.player_id.clone();
+                            ui.label(&player_id);
+
+                            let nickname = ui.add(egui::TextEdit::singleline(&mut account.nickname).desired_width(100.0));
+                            if nickname.lost_focus() {
+                                actions.push((player_id.clone(), AccountAction::Rename(account.nickname.clone())));
+                            }
+
+                            ui.label(account.region.map(region_name).unwrap_or_else(|| t!("label.account_unknown")));
+                            ui.label(
+                                account
+                                    .last_fetch
+                                    .map(|time| time_zone.format(&time))
+                                    .unwrap_or_else(|| t!("label.account_unknown")),
+                            );
+
+                            if account.corrupted {
+                                ui.colored_label(ui.visuals().error_fg_color, t!("label.invalid_player"));
+                            } else {
+                                let detail = account
+                                    .record_counts
+                                    .iter()
+                                    .map(|(card_pool_type, count)| format!("{}: {}", card_pool_name(*card_pool_type), count))
+                                    .collect::<Vec<_>>()
+                                    .join("\n");
+                                let records = ui.label(account.record_count().to_string());
+                                if !detail.is_empty() {
+                                    records.on_hover_text(detail);
+                                }
+                            }
+
+                            if ui.checkbox(&mut account.hidden, "").changed() {
+                                actions.push((player_id.clone(), AccountAction::SetHidden(account.hidden)));
+                            }
+
+                            ui.horizontal(|ui| {
+                                let url = self.relink_urls.entry(player_id.clone()).or_default();
+                                ui.add(
+                                    egui::TextEdit::singleline(url)
+                                        .hint_text("https://aki-gm-resources.aki-game.com/...")
+                                        .desired_width(160.0),
+                                );
+                                let relink_button = ui.add_enabled(!url.trim().is_empty(), egui::Button::new(t!("button.relink")));
+                                if relink_button.clicked() {
+                                    actions.push((player_id.clone(), AccountAction::Relink(std::mem::take(url))));
+                                }
+                            });
+
+                            if self.pending_delete.as_ref() == Some(&player_id) {
+                                ui.horizontal(|ui| {
+                                    if ui.button(t!("button.confirm_delete")).clicked() {
+                                        actions.push((player_id.clone(), AccountAction::Delete));
+                                        self.pending_delete = None;
+                                    }
+                                    if ui.button(t!("button.cancel")).clicked() {
+                                        self.pending_delete = None;
+                                    }
+                                });
+                            } else if ui.button(t!("button.delete")).clicked() {
+                                self.pending_delete = Some(player_id.clone());
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+
+                if let Some(player_id) = &self.pending_delete {
+                    ui.colored_label(ui.visuals().warn_fg_color, t!("label.account_delete_confirm", uid = player_id));
+                }
+
+                ui.label("");
+                if ui.button(t!("button.back")).clicked() {
+                    self.pending_delete = None;
+                    self.view = View::Home;
+                }
+            });
+        });
+
+        for (player_id, action) in actions {
+            let _ = self.view_tx.send(Command::UpdateAccount(player_id, action));
+        }
+    }
+
+    fn export_options(&self) -> ExportOptions {
+        ExportOptions { mask_uid: self.export_mask_uid, time_zone: self.time_zone }
+    }
+
+    fn export_summary(&self, format: ExportFormat) {
+        let player_id = self.player_id_selected.clone();
+        let options = self.export_options();
+        let file_name = if options.mask_uid { mask_uid(&player_id) } else { player_id.clone() };
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter(format.extension(), &[format.extension()])
+            .set_file_name(format!("{}.{}", file_name, format.extension()))
+            .save_file()
+        {
+            let _ = self.view_tx.send(Command::ExportSummary(player_id, format, options, path));
+        }
+    }
+
+    fn load_aggregate(&self) {
+        // 已隐藏或删除的用户不参与汇总
+        let player_ids = self
+            .aggregate_selected
+            .iter()
+            .filter(|player_id| self.player_id_vec.contains(player_id))
+            .cloned()
+            .collect();
+        let _ = self.view_tx.send(Command::LoadAggregate(player_ids));
+    }
+
+    fn aggregate_view(&mut self, ui: &mut Ui) {
+        ui.vertical_centered_justified(|ui| {
+            ui.group(|ui| {
+                let mut selection_changed = false;
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(t!("label.aggregate_accounts"));
+                    for player_id in self.player_id_vec.clone() {
+                        let mut selected = self.aggregate_selected.contains(&player_id);
+                        if ui.checkbox(&mut selected, self.account_label(&player_id)).changed() {
+                            selection_changed = true;
+                            if selected {
+                                self.aggregate_selected.insert(player_id);
+                            } else {
+                                self.aggregate_selected.remove(&player_id);
+                            }
+                        }
+                    }
+                });
+                if selection_changed {
+                    self.load_aggregate();
+                }
+                ui.separator();
+
+                if self.aggregate.overall.accounts.is_empty() {
+                    ui.label(t!("label.no_aggregate"));
+                } else {
+                    egui::ScrollArea::vertical().max_height(ui.available_height() - 40.0).show(ui, |ui| {
+                        egui::Grid::new("aggregate").num_columns(6).striped(true).spacing([20.0, 6.0]).show(ui, |ui| {
+                            ui.strong(t!("label.aggregate_pool"));
+                            ui.strong(t!("label.account_uid"));
+                            ui.strong(t!("label.aggregate_total"));
+                            ui.strong(t!("label.five_star"));
+                            ui.strong(t!("label.aggregate_average_pity"));
+                            ui.strong(t!("label.aggregate_current_pity"));
+                            ui.end_row();
+
+                            let pools = std::iter::once((t!("label.aggregate_overall"), &self.aggregate.overall)).chain(
+                                self.aggregate
+                                    .pools
+                                    .iter()
+                                    .map(|(card_pool_type, pool)| (card_pool_name(*card_pool_type), pool)),
+                            );
+                            for (pool_name, pool) in pools {
+                                ui.strong(pool_name);
+                                ui.strong(t!("label.aggregate_sum"));
+                                summary_row(ui, &pool.summary);
+
+                                for (player_id, summary) in pool.accounts.iter() {
+                                    ui.label("");
+                                    ui.label(self.account_label(player_id));
+                                    summary_row(ui, summary);
+                                }
+                            }
+                        });
+                    });
+                }
+
+                ui.label("");
+                if ui.button(t!("button.back")).clicked() {
+                    self.view = View::Home;
+                }
+            });
+        });
+    }
+
+    fn load_comparison(&mut self) {
+        self.comparison = None;
+        if !self.compare_left.is_empty() && !self.compare_right.is_empty() {
+            let _ = self
+                .view_tx
+                .send(Command::LoadComparison(self.compare_left.clone(), self.compare_right.clone()));
+        }
+    }
+
+    fn compare_view(&mut self, ui: &mut Ui) {
+        ui.vertical_centered_justified(|ui| {
+            ui.group(|ui| {
+                let (left, right) = (self.compare_left.clone(), self.compare_right.clone());
+                ui.horizontal(|ui| {
+                    for (id_salt, selected) in [("compare_left", &mut self.compare_left), ("compare_right", &mut self.compare_right)] {
+                        ComboBox::from_id_salt(id_salt)
+                            .selected_text(account_label(&self.accounts, selected))
+                            .show_ui(ui, |ui| {
+                                for player_id in self.player_id_vec.iter() {
+                                    let label = account_label(&self.accounts, player_id);
+                                    ui.selectable_value(selected, player_id.clone(), label);
+                                }
+                            });
+                    }
+                });
+                if self.compare_left != left || self.compare_right != right {
+                    self.load_comparison();
+                }
+                ui.separator();
+
+                match &self.comparison {
+                    Some(comparison) if !comparison.pools.is_empty() => {
+                        let left_label = self.account_label(&comparison.left_id);
+                        let right_label = self.account_label(&comparison.right_id);
+                        egui::ScrollArea::vertical().max_height(ui.available_height() - 40.0).show(ui, |ui| {
+                            for (card_pool_type, pool) in comparison.pools.iter() {
+                                ui.group(|ui| {
+                                    ui.strong(card_pool_name(*card_pool_type));
+                                    comparison_grid(ui, *card_pool_type, &left_label, &right_label, pool);
+                                });
+                            }
+                        });
+                    }
+                    _ => {
+                        ui.label(t!("label.no_comparison"));
+                    }
+                }
+
+                ui.label("");
+                if ui.button(t!("button.back")).clicked() {
+                    self.view = View::Home;
+                }
+            });
+        });
+    }
+
+    fn activity_view(&mut self, ui: &mut Ui) {
+        let requested = (self.player_id_selected.clone(), self.time_zone);
+        if self.activity_requested.as_ref() != Some(&requested) {
+            self.activity = None;
+            if !requested.0.is_empty() {
+                let _ = self.view_tx.send(Command::LoadActivity(requested.0.clone(), requested.1));
+            }
+            self.activity_requested = Some(requested);
+        }
+
+        ui.vertical_centered_justified(|ui| {
+            ui.group(|ui| {
+                ui.strong(t!("label.activity_title", user = self.account_label(&self.player_id_selected)));
+
+                match &self.activity {
+                    Some(activity) if !activity.daily.is_empty() => {
+                        let cells: BTreeMap<_, _> = activity
+                            .daily
+                            .iter()
+                            .map(|(date, period)| (*date, HeatmapCell { value: period.total(), detail: activity_detail(period) }))
+                            .collect();
+                        let years = heatmap_years(&cells);
+                        if !years.contains(&self.activity_year) {
+                            self.activity_year = years.last().copied().unwrap_or_default();
+                        }
+
+                        ui.horizontal(|ui| {
+                            ui.label(t!("label.activity_year"));
+                            ComboBox::from_id_salt("activity_year")
+                                .selected_text(self.activity_year.to_string())
+                                .show_ui(ui, |ui| {
+                                    for year in years.iter().rev() {
+                                        ui.selectable_value(&mut self.activity_year, *year, year.to_string());
+                                    }
+                                });
+                        });
+
+                        let weekday_labels = t!("label.weekdays")
+                            .split_whitespace()
+                            .map(str::to_string)
+                            .chain(std::iter::repeat(String::new()))
+                            .take(7)
+                            .collect::<Vec<_>>()
+                            .try_into()
+                            .unwrap_or_default();
+                        egui::ScrollArea::horizontal().id_salt("activity_heatmap").show(ui, |ui| {
+                            CalendarHeatmap::new(self.activity_year, cells, Color32::from_rgb(64, 160, 96), weekday_labels)
+                                .show(ui);
+                        });
+
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            ui.label(t!("label.period_pulls"));
+                            ui.selectable_value(&mut self.activity_period, Period::Week, t!("label.period_week"));
+                            ui.selectable_value(&mut self.activity_period, Period::Month, t!("label.period_month"));
+                        });
+                        let periods = activity.period(self.activity_period);
+                        let date_format = match self.activity_period {
+                            Period::Month => "%Y-%m",
+                            _ => "%Y-%m-%d",
+                        };
+                        let categories = periods.keys().map(|start| start.format(date_format).to_string()).collect();
+                        let card_pool_types: BTreeSet<i32> = periods
+                            .values()
+                            .flat_map(|period| period.pulls.keys().copied())
+                            .collect();
+                        let series = card_pool_types
+                            .into_iter()
+                            .map(|card_pool_type| {
+                                let values = periods
+                                    .values()
+                                    .map(|period| period.pulls.get(&card_pool_type).copied().unwrap_or_default() as f64)
+                                    .collect();
+                                (card_pool_name(card_pool_type), pool_color(card_pool_type), values)
+                            })
+                            .collect();
+                        StackedBarChart::new("period_pulls".to_string(), categories, series).show(ui);
+                    }
+                    Some(_) => {
+                        ui.label(t!("label.no_activity"));
+                    }
+                    None => {
+                        ui.label(t!("status.loading"));
+                    }
+                }
+
+                ui.label("");
+                if ui.button(t!("button.back")).clicked() {
+                    self.view = View::Home;
+                }
+            });
+        });
+    }
+
+    fn roster_view(&mut self, ui: &mut Ui) {
+        if self.roster_requested.as_ref() != Some(&self.player_id_selected) {
+            self.roster = None;
+            if !self.player_id_selected.is_empty() {
+                let _ = self.view_tx.send(Command::LoadRoster(self.player_id_selected.clone()));
+            }
+            self.roster_requested = Some(self.player_id_selected.clone());
+        }
+        let time_zone = self.time_zone;
+
+        ui.vertical_centered_justified(|ui| {
+            ui.group(|ui| {
+                ui.strong(t!("label.roster_title", user = self.account_label(&self.player_id_selected)));
+
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.roster_kind, None, t!("label.roster_all"));
+                    ui.selectable_value(&mut self.roster_kind, Some(ResourceKind::Resonator), t!("label.roster_resonator"));
+                    ui.selectable_value(&mut self.roster_kind, Some(ResourceKind::Weapon), t!("label.roster_weapon"));
+                    ui.separator();
+                    ui.selectable_value(&mut self.roster_quality, None, t!("label.roster_all"));
+                    ui.selectable_value(&mut self.roster_quality, Some(5), t!("label.five_star"));
+                    ui.selectable_value(&mut self.roster_quality, Some(4), t!("label.four_star"));
+                    ui.selectable_value(&mut self.roster_quality, Some(3), t!("label.three_star"));
+                    ui.separator();
+                    ui.add(egui::TextEdit::singleline(&mut self.roster_search).hint_text(t!("label.roster_search")).desired_width(120.0));
+                    ui.separator();
+                    let show_icons = ui.checkbox(&mut self.show_icons, t!("label.show_icons"));
+                    if show_icons.on_hover_text(t!("label.show_icons_hint", dir = ICON_DIR)).changed() {
+                        self.icon_cache.clear();
+                    }
+                });
+
+                match &self.roster {
+                    Some(roster) if !roster.entries.is_empty() => {
+                        let search = self.roster_search.trim().to_lowercase();
+                        let entries: Vec<_> = roster
+                            .entries
+                            .iter()
+                            .filter(|entry| self.roster_kind.is_none_or(|kind| entry.kind == kind))
+                            .filter(|entry| self.roster_quality.is_none_or(|quality| entry.quality_level == quality))
+                            .map(|entry| (resource_name(entry.resource_id, i18n::language(), &entry.name), entry))
+                            .filter(|(name, _)| search.is_empty() || name.to_lowercase().contains(&search))
+                            .collect();
+
+                        egui::ScrollArea::vertical().max_height(ui.available_height() - 40.0).show(ui, |ui| {
+                            let columns = if self.show_icons { 8 } else { 7 };
+                            egui::Grid::new("roster").num_columns(columns).striped(true).spacing([20.0, 6.0]).show(ui, |ui| {
+                                if self.show_icons {
+                                    ui.label("");
+                                }
+                                ui.strong(t!("export.name"));
+                                ui.strong(t!("export.quality"));
+                                ui.strong(t!("label.roster_detail"));
+                                ui.strong(t!("label.roster_copies"));
+                                ui.strong(t!("label.roster_rank"));
+                                ui.strong(t!("label.roster_first_obtained"));
+                                ui.strong(t!("label.roster_last_obtained"));
+                                ui.end_row();
+
+                                for (name, entry) in entries {
+                                    if self.show_icons {
+                                        match self.icon_cache.get(ui.ctx(), entry.resource_id) {
+                                            Some(texture) => {
+                                                ui.add(egui::Image::new(&texture).fit_to_exact_size(egui::vec2(ROSTER_ICON_SIZE, ROSTER_ICON_SIZE)));
+                                            }
+                                            None => {
+                                                ui.label("");
+                                            }
+                                        }
+                                    }
+                                    ui.colored_label(star_color(entry.quality_level, ui), name);
+                                    ui.label(format!("{}★", entry.quality_level));
+                                    // 未收录的资源没有详细信息
+                                    ui.label(resource_description(entry.resource_id).unwrap_or_else(|| "-".to_string()));
+                                    ui.label(entry.copies.to_string());
+                                    let rank = match entry.kind {
+                                        ResourceKind::Resonator => format!("S{}", entry.rank()),
+                                        ResourceKind::Weapon => format!("R{}", entry.rank()),
+                                    };
+                                    if entry.surplus() > 0 {
+                                        ui.label(format!("{} (+{})", rank, entry.surplus()));
+                                    } else {
+                                        ui.label(rank);
+                                    }
+                                    ui.label(time_zone.date(&entry.first_obtained).to_string());
+                                    ui.label(time_zone.date(&entry.last_obtained).to_string());
+                                    ui.end_row();
+                                }
+                            });
+                        });
+                    }
+                    Some(_) => {
+                        ui.label(t!("label.no_activity"));
+                    }
+                    None => {
+                        ui.label(t!("status.loading"));
+                    }
+                }
+
+                ui.label("");
+                if ui.button(t!("button.back")).clicked() {
+                    self.view = View::Home;
+                }
+            });
+        });
+    }
+
+    fn network_settings_view(&mut self, ui: &mut Ui) {
+        egui::Grid::new("network_settings").num_columns(2).spacing([20.0, 10.0]).show(ui, |ui| {
+            let source = self.update_config.source.clone();
+            ui.label(t!("label.update_source"));
+            ComboBox::from_id_salt("update_source")
+                .selected_text(update_source_name(&self.update_config.source))
+                .show_ui(ui, |ui| {
+                    for option in [
+                        UpdateSource::GitHub,
+                        UpdateSource::Mirror(String::new()),
+                        UpdateSource::Custom(String::new()),
+                    ] {
+                        let selected = std::mem::discriminant(&option) == std::mem::discriminant(&source);
+                        if ui.selectable_label(selected, update_source_name(&option)).clicked() && !selected {
+                            self.update_config.source = option;
+                        }
+                    }
+                });
+            ui.end_row();
+
+            let mut source_changed = self.update_config.source == UpdateSource::GitHub && source != UpdateSource::GitHub;
+            match &mut self.update_config.source {
+                UpdateSource::GitHub => {}
+                UpdateSource::Mirror(template) => {
+                    ui.label(t!("label.mirror_template"));
+                    let response = ui.add(
+                        egui::TextEdit::singleline(template).hint_text("https://mirror.example.com/{url}"),
+                    );
+                    source_changed = response.lost_focus();
+                    ui.end_row();
+                }
+                UpdateSource::Custom(url) => {
+                    ui.label(t!("label.custom_release_url"));
+                    let response = ui.add(
+                        egui::TextEdit::singleline(url).hint_text("https://example.com/releases.json"),
+                    );
+                    source_changed = response.lost_focus();
+                    ui.end_row();
+                }
+            }
+            if source_changed {
+                // 更新来源变更后重新检查更新
+                self.update_info = None;
+                let _ = self.view_tx.send(Command::CheckUpdate(self.update_config.clone()));
+            }
+
+            ui.label(t!("label.proxy")).on_hover_text(t!("label.proxy_hint"));
+            let proxy = ui.add(
+                egui::TextEdit::singleline(&mut self.network_config.proxy).hint_text("socks5://127.0.0.1:1080"),
+            );
+            let mut network_changed = proxy.lost_focus();
+            ui.end_row();
+
+            ui.label(t!("label.timeout"));
+            network_changed |= ui
+                .add(egui::DragValue::new(&mut self.network_config.timeout_secs).range(1..=300).suffix(" s"))
+                .changed();
+            ui.end_row();
+
+            ui.label("");
+            ui.checkbox(&mut self.network_config.auto_check_update, t!("label.auto_check_update"));
+            ui.end_row();
+
+            ui.label("");
+            if ui.button(t!("button.check_update")).clicked() {
+                self.update_info = None;
+                let _ = self.view_tx.send(Command::CheckUpdate(self.update_config.clone()));
+            }
+            ui.end_row();
+
+            if network_changed {
+                let message = match http::configure(&self.network_config) {
+                    Ok(_) => Message::info(t!("status.network_applied")),
+                    Err(err) => Message::error(&err),
+                };
+                self.set_message(message);
+            }
+        });
+    }
+
+    // 生成各卡池的统计图，已生成时直接复用，数据、选择或显示设置变化时由调用方清空
+    fn create_pool_charts(&mut self) {
+        if self.gacha_statistic_view_vec.is_empty() {
+            let mut gacha_statistic_view_vec = vec![];
+            for (card_pool_type, gacha_statistics_data) in self.gacha_statistics.iter() {
+                let pie_chart = star_pie_chart(gacha_statistics_data)
+                    .size(CARD_WIDTH, CHART_HEIGHT)
+                    .donut(self.donut_chart)
+                    .selected(self.pool_selection.get(card_pool_type).copied());
 
                 let gacha_statistic_view = GachaStatisticsView {
                     card_pool_type: *card_pool_type,
                     total: gacha_statistics_data.total,
                     pull_count: gacha_statistics_data.pull_count,
                     pie_chart,
+                    four_star_detail: gacha_statistics_data.four_star_detail.clone(),
+                    pity_histogram: pity_histogram_chart(gacha_statistics_data),
+                    pity_timeline: pity_timeline_chart(gacha_statistics_data, self.time_zone),
                     detail: gacha_statistics_data.detail.clone(),
                 };
 
@@ -557,3 +2353,74 @@ impl MainView {
         }
     }
 }
+
+// 卡池各星级占比的统计图
+fn star_pie_chart(data: &GachaStatisticsData) -> PieChart {
+    PieChart::new(
+        data.card_pool_type.to_string(),
+        vec![
+            (data.three_count as f64, t!("label.three_star"), THREE_STAR_COLOR),
+            (data.four_count as f64, t!("label.four_star"), FOUR_STAR_COLOR),
+            (data.five_count as f64, t!("label.five_star"), FIVE_STAR_COLOR),
+        ],
+    )
+}
+
+fn pity_histogram_chart(data: &GachaStatisticsData) -> PityHistogram {
+    PityHistogram::new(
+        format!("{}_pity_histogram", data.card_pool_type),
+        &data.detail,
+        t!("label.pity_actual"),
+        t!("label.pity_expected"),
+    )
+}
+
+fn pity_timeline_chart(data: &GachaStatisticsData, time_zone: DisplayTimeZone) -> PityTimeline {
+    let marker_texts = data
+        .detail
+        .iter()
+        .map(|item| {
+            let name = resource_name(item.resource_id, i18n::language(), &item.name);
+            format!("{}[{}]\n{}", name, item.count, time_zone.format(&item.time))
+        })
+        .collect();
+    PityTimeline::new(
+        format!("{}_pity_timeline", data.card_pool_type),
+        data,
+        marker_texts,
+        t!("label.pity_timeline"),
+        t!("label.five_star"),
+    )
+}
+
+#[tokio::test]
+async fn service_shutdown_test() {
+    let (event_tx, _event_rx) = unbounded_channel();
+    let (command_tx, command_rx) = unbounded_channel();
+    start_service(
+        RequestConfig::default(),
+        UiRepaintSender { sender: event_tx, ctx: egui::Context::default() },
+        command_rx,
+    );
+
+    let (ack_tx, ack_rx) = mpsc::channel();
+    command_tx.send(Command::CancelFetch).unwrap();
+    command_tx.send(Command::Shutdown(ack_tx)).unwrap();
+    let ack = tokio::task::spawn_blocking(move || ack_rx.recv_timeout(Duration::from_secs(2)))
+        .await
+        .unwrap();
+    assert!(ack.is_ok());
+
+    // 服务停止后不再接收指令
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(command_tx.send(Command::CancelFetch).is_err());
+}
+
+#[test]
+fn card_columns_test() {
+    assert_eq!(card_columns(0.0, 8.0), 1);
+    assert_eq!(card_columns(CARD_WIDTH * 2.0, 8.0), 1);
+    // 默认窗口宽度减去面板边距及滚动条
+    assert_eq!(card_columns(WindowConfig::default().width - 30.0, 8.0), 3);
+    assert_eq!(card_columns(1900.0, 8.0), 6);
+}
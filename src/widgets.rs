@@ -1 +1,5 @@
-pub(crate) mod pie_chart;
\ No newline at end of file
+pub(crate) mod pie_chart;
+pub(crate) mod pity_histogram;
+pub(crate) mod pity_timeline;
+pub(crate) mod calendar_heatmap;
+pub(crate) mod stacked_bar_chart;
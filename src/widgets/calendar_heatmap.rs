@@ -0,0 +1,127 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate};
+use egui::{Align2, Color32, FontId, Pos2, Rect, Sense, Stroke, Vec2};
+
+const CELL_SIZE: f32 = 11.0;
+const CELL_GAP: f32 = 2.0;
+// 左侧星期及顶部月份标签所占的空间
+const LABEL_WIDTH: f32 = 28.0;
+const LABEL_HEIGHT: f32 = 14.0;
+
+/// 日历中一天的数据
+pub(crate) struct HeatmapCell {
+    pub(crate) value: i32,
+    // 鼠标悬停时显示的说明
+    pub(crate) detail: String,
+}
+
+/// 按周排列一年中每一天的热力图，颜色深浅表示数值大小
+pub(crate) struct CalendarHeatmap {
+    year: i32,
+    cells: BTreeMap<NaiveDate, HeatmapCell>,
+    max: i32,
+    color: Color32,
+    // 星期一至星期日的标签，只显示其中几个
+    weekday_labels: [String; 7],
+}
+
+impl CalendarHeatmap {
+    pub fn new(year: i32, cells: BTreeMap<NaiveDate, HeatmapCell>, color: Color32, weekday_labels: [String; 7]) -> Self {
+        let max = cells
+            .iter()
+            .filter(|(date, _)| date.year() == year)
+            .map(|(_, cell)| cell.value)
+            .max()
+            .unwrap_or_default();
+
+        Self {
+            year,
+            cells,
+            max,
+            color,
+            weekday_labels,
+        }
+    }
+
+    pub fn show(&self, ui: &mut egui::Ui) {
+        let (Some(first_day), Some(last_day)) = (
+            NaiveDate::from_ymd_opt(self.year, 1, 1),
+            NaiveDate::from_ymd_opt(self.year, 12, 31),
+        ) else {
+            return;
+        };
+        let offset = first_day.weekday().num_days_from_monday();
+        let weeks = (last_day.ordinal0() + offset) / 7 + 1;
+
+        let size = Vec2::new(
+            LABEL_WIDTH + weeks as f32 * (CELL_SIZE + CELL_GAP),
+            LABEL_HEIGHT + 7.0 * (CELL_SIZE + CELL_GAP),
+        );
+        let (rect, response) = ui.allocate_exact_size(size, Sense::hover());
+        let painter = ui.painter_at(rect);
+        let text_color = ui.visuals().weak_text_color();
+        let empty_color = ui.visuals().extreme_bg_color;
+        let font = FontId::proportional(9.0);
+
+        // 日期所在的格子
+        let cell_rect = |date: NaiveDate| {
+            let index = date.ordinal0() + offset;
+            let min = rect.min
+                + Vec2::new(
+                    LABEL_WIDTH + (index / 7) as f32 * (CELL_SIZE + CELL_GAP),
+                    LABEL_HEIGHT + (index % 7) as f32 * (CELL_SIZE + CELL_GAP),
+                );
+            Rect::from_min_size(min, Vec2::splat(CELL_SIZE))
+        };
+
+        for (row, label) in self.weekday_labels.iter().enumerate().filter(|(row, _)| row % 2 == 0) {
+            let y = rect.min.y + LABEL_HEIGHT + row as f32 * (CELL_SIZE + CELL_GAP) + CELL_SIZE / 2.0;
+            painter.text(Pos2::new(rect.min.x, y), Align2::LEFT_CENTER, label, font.clone(), text_color);
+        }
+
+        let mut hovered = None;
+        for date in first_day.iter_days().take_while(|date| *date <= last_day) {
+            let cell = cell_rect(date);
+            if date.day() == 1 {
+                painter.text(
+                    Pos2::new(cell.min.x, rect.min.y),
+                    Align2::LEFT_TOP,
+                    date.month().to_string(),
+                    font.clone(),
+                    text_color,
+                );
+            }
+
+            let value = self.cells.get(&date).map(|cell| cell.value).unwrap_or_default();
+            let fill = if value > 0 && self.max > 0 {
+                self.color.gamma_multiply(0.25 + 0.75 * value as f32 / self.max as f32)
+            } else {
+                empty_color
+            };
+            painter.rect_filled(cell, 2.0, fill);
+
+            if response.hover_pos().is_some_and(|pos| cell.contains(pos)) {
+                painter.rect_stroke(cell, 2.0, Stroke::new(1.0, text_color), egui::StrokeKind::Outside);
+                hovered = Some(date);
+            }
+        }
+
+        if let Some(date) = hovered {
+            let detail = self.cells.get(&date).map(|cell| cell.detail.as_str()).unwrap_or_default();
+            response.on_hover_ui_at_pointer(|ui| {
+                ui.strong(date.format("%Y-%m-%d").to_string());
+                if !detail.is_empty() {
+                    ui.label(detail);
+                }
+            });
+        }
+    }
+}
+
+/// 数据中包含的年份，按时间顺序排列
+pub(crate) fn heatmap_years(cells: &BTreeMap<NaiveDate, HeatmapCell>) -> Vec<i32> {
+    let mut years: Vec<_> = cells.keys().map(|date| date.year()).collect();
+    years.dedup();
+    years
+}
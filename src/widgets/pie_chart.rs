@@ -1,14 +1,24 @@
 use std::f64::consts::TAU;
 
 use egui::{Align2, Color32, RichText, Stroke};
-use egui_plot::{Legend, Plot, PlotPoint, PlotPoints, Polygon, Text};
+use egui_plot::{Plot, PlotPoint, PlotPoints, Polygon, Text};
 
 const FULL_CIRCLE_VERTICES: f64 = 360.0;
 const RADIUS: f64 = 1.0;
+// 环形图内圈半径
+const DONUT_RADIUS: f64 = 0.55;
+// 占比低于该值的扇形不显示百分比，避免文字重叠
+const MIN_LABEL_PERCENT: f64 = 0.05;
 
 pub(crate) struct PieChart {
     name: String,
     sectors: Vec<Sector>,
+    total: f64,
+    width: f32,
+    height: f32,
+    donut: bool,
+    // 当前选中的数据下标，与创建时传入的数据顺序一致
+    selected: Option<usize>,
 }
 
 impl PieChart {
@@ -17,11 +27,12 @@ impl PieChart {
 
         let slices: Vec<_> = data
             .into_iter()
-            .filter_map(|(number, name, color)| {
+            .enumerate()
+            .filter_map(|(index, (number, name, color))| {
                 if number == 0.0 {
                     None
                 } else {
-                    Some((number, number / sum, name, color))
+                    Some((index, number, number / sum, name, color))
                 }
             })
             .collect();
@@ -32,13 +43,13 @@ impl PieChart {
 
         let sectors = slices
             .into_iter()
-            .map(|(number, percent, name, color)| {
+            .map(|(index, number, percent, name, color)| {
                 let vertices = (FULL_CIRCLE_VERTICES * percent).floor() as usize;
 
                 let start = TAU * offset;
                 let end = TAU * (offset + percent);
 
-                let sector = Sector::new(number, name, start, end, vertices, step, color);
+                let sector = Sector::new(index, number, percent, name, start, end, vertices, step, color);
 
                 offset += percent;
 
@@ -46,80 +57,171 @@ impl PieChart {
             })
             .collect();
 
-        Self { name, sectors }
+        Self {
+            name,
+            sectors,
+            total: sum,
+            width: 285.0,
+            height: 150.0,
+            donut: false,
+            selected: None,
+        }
+    }
+
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// 以环形图显示，中心显示总数
+    pub fn donut(mut self, donut: bool) -> Self {
+        self.donut = donut;
+        self
+    }
+
+    pub fn selected(mut self, selected: Option<usize>) -> Self {
+        self.selected = selected;
+        self
     }
 
-    pub fn show(&mut self, ui: &mut egui::Ui) {
+    /// 显示饼图及图例，返回本次点击的扇形或图例对应的数据下标
+    pub fn show(&mut self, ui: &mut egui::Ui) -> Option<usize> {
         let sectors = self.sectors.clone();
+        let selected = self.selected;
+        let donut = self.donut;
+        let total = self.total;
+        let background = ui.visuals().panel_fill;
+        let text_color = ui.visuals().text_color();
 
-        Plot::new(&self.name)
+        let response = Plot::new(&self.name)
             .label_formatter(|_: &str, _: &PlotPoint| String::default())
             .show_background(false)
-            .legend(Legend::default().hidden_items(vec![]))
             .show_axes([false, false])
             .show_grid(false)
             .allow_boxed_zoom(false)
             .allow_drag(false)
             .allow_zoom(false)
             .allow_scroll(false)
+            .allow_double_click_reset(false)
             .data_aspect(1.0)
             // 显示范围略大于圆半径
             .include_x(-1.1 * RADIUS)
             .include_x(1.1 * RADIUS)
             .include_y(-1.1 * RADIUS)
             .include_y(1.1 * RADIUS)
-            .height(150.0)
-            .width(285.0)
+            .height(self.height)
+            .width(self.width)
             .show(ui, |plot_ui| {
-                for sector in sectors.into_iter() {
-                    let highlight = plot_ui
-                        .pointer_coordinate()
-                        .map(|p| sector.contains(&p))
-                        .unwrap_or_default();
-
-                    let Sector {
-                        name,
-                        number,
-                        points,
-                        color,
-                        ..
-                    } = sector;
+                let pointer = plot_ui.pointer_coordinate();
+                let hovered = pointer.and_then(|point| sectors.iter().find(|sector| sector.contains(&point, donut)));
+                let clicked = hovered
+                    .filter(|_| plot_ui.response().clicked())
+                    .map(|sector| sector.index);
+                let hovered = hovered.cloned();
+
+                for sector in sectors.iter() {
+                    // 选中的扇形加粗边框，其余扇形在有选中项时变淡
+                    let alpha = if selected.is_none() || selected == Some(sector.index) { 200 } else { 80 };
+                    let stroke_width = if selected == Some(sector.index) { 3.0 } else { 1.5 };
+                    let color = sector.color;
 
                     plot_ui.polygon(
-                        Polygon::new(&name, PlotPoints::new(points))
-                            .fill_color(Color32::from_rgba_premultiplied(
-                                color.r(),
-                                color.g(),
-                                color.b(),
-                                200,
-                            ))
-                            .stroke(Stroke { width: 1.5, color })
+                        Polygon::new(&sector.name, PlotPoints::new(sector.points.clone()))
+                            .fill_color(Color32::from_rgba_unmultiplied(color.r(), color.g(), color.b(), alpha))
+                            .stroke(Stroke { width: stroke_width, color })
                             .highlight(false),
                     );
 
-                    if highlight {
-                        let mut point = plot_ui.pointer_coordinate().unwrap();
-                        // 防止文字与轴线重叠
-                        point = PlotPoint::new(point.x + 0.07, point.y - 0.07);
-
-                        let text = RichText::new(format!(" {}\n {}", name, number)).size(15.0);
-
+                    if sector.percent >= MIN_LABEL_PERCENT {
+                        let (x, y) = sector.label_position(donut);
                         plot_ui.text(
-                            Text::new(&name, point, text)
-                                .anchor(Align2::LEFT_TOP)
-                                // 解决字体被填充颜色覆盖问题
-                                .highlight(true),
+                            Text::new(
+                                format!("{}_percent", sector.name),
+                                PlotPoint::new(x, y),
+                                RichText::new(format_percent(sector.percent)).size(11.0).color(text_color),
+                            )
+                            .highlight(true),
                         );
                     }
                 }
+
+                if donut {
+                    // 用背景色覆盖中心部分形成环形
+                    let hole: Vec<[f64; 2]> = (0..FULL_CIRCLE_VERTICES as usize)
+                        .map(|v| {
+                            let t = TAU * v as f64 / FULL_CIRCLE_VERTICES;
+                            [DONUT_RADIUS * t.sin(), DONUT_RADIUS * t.cos()]
+                        })
+                        .collect();
+                    plot_ui.polygon(
+                        Polygon::new("donut_hole", PlotPoints::new(hole))
+                            .fill_color(background)
+                            .stroke(Stroke::NONE)
+                            .highlight(false),
+                    );
+                    plot_ui.text(
+                        Text::new(
+                            "donut_total",
+                            PlotPoint::new(0.0, 0.0),
+                            RichText::new(format!("{}", total)).size(16.0).strong().color(text_color),
+                        )
+                        .highlight(true),
+                    );
+                }
+
+                if let (Some(sector), Some(point)) = (hovered, pointer) {
+                    // 防止文字与轴线重叠
+                    let point = PlotPoint::new(point.x + 0.07, point.y - 0.07);
+
+                    let text = RichText::new(format!(
+                        " {}\n {} ({})",
+                        sector.name,
+                        sector.number,
+                        format_percent(sector.percent)
+                    ))
+                    .size(15.0);
+
+                    plot_ui.text(
+                        Text::new(&sector.name, point, text)
+                            .anchor(Align2::LEFT_TOP)
+                            // 解决字体被填充颜色覆盖问题
+                            .highlight(true),
+                    );
+                }
+
+                clicked
             });
+
+        let mut clicked = response.inner;
+
+        // 图例可通过键盘聚焦并以回车或空格选择
+        ui.horizontal_wrapped(|ui| {
+            ui.set_max_width(self.width);
+            for sector in self.sectors.iter() {
+                let text = RichText::new(format!("■ {} {}", sector.name, format_percent(sector.percent)));
+                let legend = ui.selectable_label(selected == Some(sector.index), text.color(sector.color));
+                if legend.clicked() {
+                    clicked = Some(sector.index);
+                }
+            }
+        });
+
+        clicked
     }
 }
 
+fn format_percent(percent: f64) -> String {
+    format!("{:.1}%", percent * 100.0)
+}
+
 #[derive(Clone)]
 struct Sector {
+    // 创建时传入数据中的下标
+    index: usize,
     name: String,
     number: f64,
+    percent: f64,
     start: f64,
     end: f64,
     points: Vec<[f64; 2]>,
@@ -127,8 +229,11 @@ struct Sector {
 }
 
 impl Sector {
+    #[allow(clippy::too_many_arguments)]
     pub fn new<S: AsRef<str>>(
+        index: usize,
         number: f64,
+        percent: f64,
         name: S,
         start: f64,
         end: f64,
@@ -153,8 +258,10 @@ impl Sector {
         points.push([RADIUS * end.sin(), RADIUS * end.cos()]);
 
         Self {
+            index,
             name: name.as_ref().to_string(),
             number,
+            percent,
             start,
             end,
             points,
@@ -162,8 +269,8 @@ impl Sector {
         }
     }
 
-    /// 用于判断鼠标所指的点是否在某一部分扇形区间内
-    pub fn contains(&self, &PlotPoint { x, y }: &PlotPoint) -> bool {
+    /// 用于判断鼠标所指的点是否在某一部分扇形区间内，环形图不包含中心部分
+    pub fn contains(&self, &PlotPoint { x, y }: &PlotPoint, donut: bool) -> bool {
         let r = y.hypot(x);
         let mut theta = x.atan2(y);
 
@@ -171,6 +278,14 @@ impl Sector {
             theta += TAU;
         }
 
-        r < RADIUS && theta > self.start && theta < self.end
+        let inner = if donut { DONUT_RADIUS } else { 0.0 };
+        r < RADIUS && r >= inner && theta > self.start && theta < self.end
+    }
+
+    // 百分比文字位于扇形中线上
+    fn label_position(&self, donut: bool) -> (f64, f64) {
+        let theta = (self.start + self.end) / 2.0;
+        let r = if donut { (RADIUS + DONUT_RADIUS) / 2.0 } else { RADIUS * 0.6 };
+        (r * theta.sin(), r * theta.cos())
     }
 }
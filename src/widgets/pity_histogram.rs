@@ -0,0 +1,94 @@
+use egui::Color32;
+use egui_plot::{Bar, BarChart, Legend, Line, Plot, PlotPoint};
+
+use crate::core::statistics::{pity_histogram, theoretical_pity_distribution, GachaStatisticsDataItem};
+
+// 每组包含的抽数
+const BIN_WIDTH: i32 = 5;
+
+pub(crate) struct PityHistogram {
+    name: String,
+    width: f32,
+    height: f32,
+    bar_label: String,
+    expected_label: String,
+    bars: Vec<(f64, f64)>,
+    // 按理论分布计算的各组期望出金次数
+    expected: Vec<[f64; 2]>,
+}
+
+impl PityHistogram {
+    pub fn new(name: String, detail: &[GachaStatisticsDataItem], bar_label: String, expected_label: String) -> Self {
+        let bins = pity_histogram(detail, BIN_WIDTH);
+        let distribution = theoretical_pity_distribution();
+        let five_count = detail.len() as f64;
+
+        let center = |start: i32| start as f64 + (BIN_WIDTH - 1) as f64 / 2.0;
+        let bars = bins.iter().map(|(start, count)| (center(*start), *count as f64)).collect();
+        let expected = bins
+            .iter()
+            .map(|(start, _)| {
+                let probability: f64 = distribution
+                    .iter()
+                    .skip((*start - 1) as usize)
+                    .take(BIN_WIDTH as usize)
+                    .sum();
+                [center(*start), probability * five_count]
+            })
+            .collect();
+
+        Self {
+            name,
+            width: 285.0,
+            height: 150.0,
+            bar_label,
+            expected_label,
+            bars,
+            expected,
+        }
+    }
+
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn show(&self, ui: &mut egui::Ui) {
+        let bars = self
+            .bars
+            .iter()
+            .map(|(x, count)| Bar::new(*x, *count).width(BIN_WIDTH as f64 * 0.9))
+            .collect();
+        let bar_label = self.bar_label.clone();
+
+        Plot::new(&self.name)
+            .label_formatter(move |name: &str, point: &PlotPoint| {
+                if name == bar_label {
+                    // 显示鼠标所在分组的抽数范围
+                    let start = (point.x - (BIN_WIDTH - 1) as f64 / 2.0).round() as i32;
+                    format!("{}-{}: {:.0}", start, start + BIN_WIDTH - 1, point.y)
+                } else {
+                    format!("{:.0}: {:.2}", point.x, point.y)
+                }
+            })
+            .legend(Legend::default())
+            .show_background(false)
+            .allow_boxed_zoom(false)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .include_x(0.0)
+            .include_y(0.0)
+            .height(self.height)
+            .width(self.width)
+            .show(ui, |plot_ui| {
+                plot_ui.bar_chart(BarChart::new(&self.bar_label, bars).color(Color32::from_rgb(225, 216, 115)));
+                plot_ui.line(
+                    Line::new(&self.expected_label, self.expected.clone())
+                        .color(Color32::from_rgb(99, 176, 225))
+                        .width(1.5),
+                );
+            });
+    }
+}
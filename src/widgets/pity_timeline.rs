@@ -0,0 +1,87 @@
+use egui::Color32;
+use egui_plot::{Legend, Line, MarkerShape, Plot, PlotPoint, Points};
+
+use crate::core::statistics::{pity_timeline, GachaStatisticsData};
+
+pub(crate) struct PityTimeline {
+    name: String,
+    width: f32,
+    height: f32,
+    line_label: String,
+    marker_label: String,
+    points: Vec<[f64; 2]>,
+    // 五星点位及鼠标悬停时显示的说明，与记录顺序一致
+    markers: Vec<([f64; 2], String)>,
+}
+
+impl PityTimeline {
+    /// marker_texts 为各五星的说明文字，顺序与 data.detail 一致
+    pub fn new(
+        name: String,
+        data: &GachaStatisticsData,
+        marker_texts: Vec<String>,
+        line_label: String,
+        marker_label: String,
+    ) -> Self {
+        let (points, five_stars) = pity_timeline(data);
+        let markers = five_stars.into_iter().zip(marker_texts).collect();
+
+        Self {
+            name,
+            width: 285.0,
+            height: 150.0,
+            line_label,
+            marker_label,
+            points,
+            markers,
+        }
+    }
+
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn show(&self, ui: &mut egui::Ui) {
+        let marker_label = self.marker_label.clone();
+        let markers = self.markers.clone();
+
+        Plot::new(&self.name)
+            .label_formatter(move |name: &str, point: &PlotPoint| {
+                let marker = markers
+                    .iter()
+                    .find(|([x, y], _)| *x == point.x && *y == point.y)
+                    .filter(|_| name == marker_label);
+                match marker {
+                    Some((_, text)) => text.clone(),
+                    None => format!("{:.0}: {:.0}", point.x, point.y),
+                }
+            })
+            .legend(Legend::default())
+            .show_background(false)
+            .allow_boxed_zoom(false)
+            .allow_scroll(false)
+            .include_x(0.0)
+            .include_y(0.0)
+            .height(self.height)
+            .width(self.width)
+            .show(ui, |plot_ui| {
+                plot_ui.line(
+                    Line::new(&self.line_label, self.points.clone())
+                        .color(Color32::from_rgb(171, 101, 207))
+                        .width(1.5),
+                );
+                plot_ui.points(
+                    Points::new(
+                        &self.marker_label,
+                        self.markers.iter().map(|(point, _)| *point).collect::<Vec<_>>(),
+                    )
+                    .shape(MarkerShape::Diamond)
+                    .filled(true)
+                    .radius(4.0)
+                    .color(Color32::from_rgb(225, 216, 115)),
+                );
+            });
+    }
+}
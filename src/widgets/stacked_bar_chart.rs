@@ -0,0 +1,70 @@
+use egui::Color32;
+use egui_plot::{Bar, BarChart, Legend, Plot, PlotPoint};
+
+/// 多个系列叠加显示的柱状图，横轴为分类
+pub(crate) struct StackedBarChart {
+    name: String,
+    categories: Vec<String>,
+    // 系列名称、颜色及各分类的数值
+    series: Vec<(String, Color32, Vec<f64>)>,
+}
+
+impl StackedBarChart {
+    pub fn new(name: String, categories: Vec<String>, series: Vec<(String, Color32, Vec<f64>)>) -> Self {
+        Self {
+            name,
+            categories,
+            series,
+        }
+    }
+
+    pub fn show(&self, ui: &mut egui::Ui) {
+        let categories = self.categories.clone();
+        let label_categories = self.categories.clone();
+
+        let mut charts: Vec<BarChart> = vec![];
+        for (name, color, values) in self.series.iter() {
+            let bars = values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| Bar::new(index as f64, *value).width(0.7).name(&categories[index]))
+                .collect();
+            let chart = BarChart::new(name, bars).color(*color);
+            let below: Vec<&BarChart> = charts.iter().collect();
+            let chart = chart.stack_on(&below);
+            charts.push(chart);
+        }
+
+        Plot::new(&self.name)
+            .x_axis_formatter(move |mark, _| {
+                // 只在整数位置显示分类名称
+                if mark.value.fract() == 0.0 && mark.value >= 0.0 {
+                    categories.get(mark.value as usize).cloned().unwrap_or_default()
+                } else {
+                    String::new()
+                }
+            })
+            .label_formatter(move |name: &str, point: &PlotPoint| {
+                let category = label_categories
+                    .get(point.x.round().max(0.0) as usize)
+                    .cloned()
+                    .unwrap_or_default();
+                if name.is_empty() {
+                    category
+                } else {
+                    format!("{}\n{}", name, category)
+                }
+            })
+            .legend(Legend::default())
+            .show_background(false)
+            .allow_boxed_zoom(false)
+            .allow_scroll(false)
+            .include_y(0.0)
+            .height(220.0)
+            .show(ui, |plot_ui| {
+                for chart in charts {
+                    plot_ui.bar_chart(chart);
+                }
+            });
+    }
+}